@@ -0,0 +1,381 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+pub type Card = char;
+pub type Pattern = Vec<u64>;
+
+// Group sizes a hand needs to reach, largest first. An empty pattern matches any hand
+pub struct HandKind
+{
+    pub name: String,
+    pub pattern: Pattern
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum TieBreak
+{
+    // Cards are compared in the order they were dealt
+    Positional,
+    // Cards are compared largest group first, higher ranks first within equally sized groups
+    RankBased
+}
+
+pub trait FindType
+{
+    fn find_type(&self, cards: &[Card]) -> usize;
+    fn kind(&self, hand_type: usize) -> &HandKind;
+    // Concrete cards standing in for any wildcards while still reaching the hand's type
+    fn substitute(&self, cards: &[Card], order: &dyn CompareCard) -> Vec<Card>;
+}
+
+pub trait CompareCard
+{
+    fn rank(&self, card: &Card) -> Option<usize>;
+    fn cards(&self) -> Vec<Card>;
+
+    fn compare_card(&self, lhs: &Card, rhs: &Card) -> Ordering
+    {
+        return self.rank(lhs).cmp(&self.rank(rhs));
+    }
+}
+
+// Hand types from weakest to strongest. A hand takes the strongest type whose pattern it covers,
+// with wildcards free to join any group or start new ones
+pub struct TypeTable
+{
+    kinds: Vec<HandKind>,
+    wildcards: Vec<Card>
+}
+
+// Card ranks from weakest to strongest
+pub struct RankOrder
+{
+    ranks: HashMap<Card, usize>
+}
+
+pub struct Rules
+{
+    pub hand_size: usize,
+    pub find_type: Box<dyn FindType>,
+    pub compare_card: Box<dyn CompareCard>,
+    pub tie_break: TieBreak
+}
+
+pub struct Hand
+{
+    pub cards: Vec<Card>,
+    pub hand_type: usize,
+    pub bid: u64
+}
+
+// How a hand ended up where it is, relative to the hand ranked right below it
+pub struct Explanation
+{
+    pub rank: usize,
+    pub cards: Vec<Card>,
+    pub substitution: Vec<Card>,
+    pub kind: String,
+    pub bid: u64,
+    pub decided_by: String
+}
+
+// Group each wildcard joined, where groups past the natural ones are new groups
+type Assignment = Vec<usize>;
+
+fn covers(counts: &Pattern, pattern: &Pattern) -> bool
+{
+    return pattern.len() <= counts.len() && counts.iter().zip(pattern.iter()).all(|(count, needed)| count >= needed);
+}
+
+fn sorted(mut counts: Pattern) -> Pattern
+{
+    counts.sort_by(|lhs, rhs| rhs.cmp(lhs));
+    return counts;
+}
+
+// Every way of handing out the wildcards, one at a time to groups in non-decreasing order so each split is only built once
+fn distribute(counts: &mut Pattern, assignment: &mut Assignment, wildcards: u64, start: usize, candidates: &mut Vec<(Pattern, Assignment)>)
+{
+    if wildcards == 0
+    {
+        candidates.push((sorted(counts.clone()), assignment.clone()));
+        return;
+    }
+
+    for i in start..counts.len()
+    {
+        counts[i] += 1;
+        assignment.push(i);
+        distribute(counts, assignment, wildcards - 1, i, candidates);
+        assignment.pop();
+        counts[i] -= 1;
+    }
+
+    counts.push(1);
+    assignment.push(counts.len() - 1);
+    distribute(counts, assignment, wildcards - 1, counts.len() - 1, candidates);
+    assignment.pop();
+    counts.pop();
+}
+
+impl TypeTable
+{
+    pub fn new(kinds: Vec<HandKind>, wildcards: Vec<Card>) -> TypeTable
+    {
+        return TypeTable{kinds: kinds, wildcards: wildcards};
+    }
+
+    pub fn is_wildcard(&self, card: &Card) -> bool
+    {
+        return self.wildcards.contains(card);
+    }
+
+    fn classify(&self, counts: &Pattern) -> usize
+    {
+        return (0..self.kinds.len()).rev().find(|&i| covers(counts, &self.kinds[i].pattern)).unwrap_or(0);
+    }
+
+    // Natural cards with their group sizes, largest group first, along with the number of wildcards
+    fn group(&self, cards: &[Card]) -> (Vec<(Card, u64)>, u64)
+    {
+        let mut groups: Vec<(Card, u64)> = Vec::new();
+        let mut wildcards: u64 = 0;
+
+        for card in cards
+        {
+            if self.is_wildcard(card)
+            {
+                wildcards += 1;
+                continue;
+            }
+
+            match groups.iter_mut().find(|(it, _)| it == card)
+            {
+                Some(group) => group.1 += 1,
+                None => groups.push((*card, 1))
+            }
+        }
+
+        groups.sort_by(|lhs, rhs| rhs.1.cmp(&lhs.1));
+        return (groups, wildcards);
+    }
+
+    // Strongest type reachable by the wildcards, with the first assignment reaching it
+    fn best_assignment(&self, counts: &Pattern, wildcards: u64) -> (usize, Assignment)
+    {
+        let mut candidates: Vec<(Pattern, Assignment)> = Vec::new();
+        distribute(&mut counts.clone(), &mut Assignment::new(), wildcards, 0, &mut candidates);
+
+        let mut best: (usize, Assignment) = (0, Assignment::new());
+
+        for (i, (pattern, assignment)) in candidates.into_iter().enumerate()
+        {
+            let hand_type = self.classify(&pattern);
+
+            if i == 0 || hand_type > best.0
+            {
+                best = (hand_type, assignment);
+            }
+        }
+
+        return best;
+    }
+}
+
+impl FindType for TypeTable
+{
+    fn find_type(&self, cards: &[Card]) -> usize
+    {
+        let (groups, wildcards) = self.group(cards);
+        return self.best_assignment(&groups.iter().map(|group| group.1).collect(), wildcards).0;
+    }
+
+    fn kind(&self, hand_type: usize) -> &HandKind
+    {
+        return &self.kinds[hand_type];
+    }
+
+    // Wildcards joining a group copy its card, new groups take the highest ranked cards not already in the hand
+    fn substitute(&self, cards: &[Card], order: &dyn CompareCard) -> Vec<Card>
+    {
+        let (groups, wildcards) = self.group(cards);
+        let (_, assignment) = self.best_assignment(&groups.iter().map(|group| group.1).collect(), wildcards);
+
+        let unused: Vec<Card> = order.cards().into_iter().rev()
+            .filter(|card| !self.is_wildcard(card) && !cards.contains(card))
+            .collect();
+
+        let mut replacements = assignment.iter().map(|&group| {
+            if group < groups.len() { groups[group].0 } else { unused.get(group - groups.len()).copied().unwrap_or('?') }
+        });
+
+        return cards.iter().map(|card| if self.is_wildcard(card) { replacements.next().unwrap() } else { *card }).collect();
+    }
+}
+
+impl RankOrder
+{
+    pub fn new(order: &str) -> RankOrder
+    {
+        return RankOrder{ranks: order.chars().enumerate().map(|(i, card)| (card, i)).collect()};
+    }
+}
+
+impl CompareCard for RankOrder
+{
+    fn rank(&self, card: &Card) -> Option<usize>
+    {
+        return self.ranks.get(card).copied();
+    }
+
+    fn cards(&self) -> Vec<Card>
+    {
+        let mut cards: Vec<Card> = self.ranks.keys().copied().collect();
+        cards.sort_by_key(|card| self.ranks[card]);
+        return cards;
+    }
+}
+
+// Pattern list such as "2,2+2,3,3+2,4,5" from weakest to strongest. A high card type is always added below them
+pub fn parse_types(types: &str) -> Result<Vec<HandKind>, String>
+{
+    let mut kinds: Vec<HandKind> = vec![HandKind{name: String::from("HighCard"), pattern: Pattern::new()}];
+
+    for pattern_str in types.split(",")
+    {
+        let pattern = pattern_str.split("+")
+            .map(|count| count.trim().parse::<u64>().map_err(|_| format!("Invalid group size \"{}\"", count)))
+            .collect::<Result<Pattern, String>>()?;
+
+        kinds.push(HandKind{name: pattern_str.to_string(), pattern: sorted(pattern)});
+    }
+
+    return Ok(kinds);
+}
+
+impl Rules
+{
+    pub fn parse_hand(&self, line: &str) -> Result<Hand, String>
+    {
+        let (hand_str, bid_str) = line.split_once(" ").ok_or(format!("Missing bid in \"{}\"", line))?;
+        let cards: Vec<Card> = hand_str.chars().collect();
+
+        if cards.len() != self.hand_size
+        {
+            return Err(format!("Hand \"{}\" should have {} cards", hand_str, self.hand_size));
+        }
+
+        let unknown_option = cards.iter().find(|card| self.compare_card.rank(card).is_none());
+
+        if unknown_option.is_some()
+        {
+            return Err(format!("Unknown card '{}' in \"{}\"", unknown_option.unwrap(), hand_str));
+        }
+
+        let bid: u64 = bid_str.trim().parse::<u64>().map_err(|_| format!("Invalid bid in \"{}\"", line))?;
+        let hand_type = self.find_type.find_type(&cards);
+
+        return Ok(Hand{cards: cards, hand_type: hand_type, bid: bid});
+    }
+
+    pub fn parse_hands(&self, contents: &str) -> Result<Vec<Hand>, String>
+    {
+        return contents.lines().map(|line| self.parse_hand(line)).collect();
+    }
+
+    fn tie_break_order(&self, cards: &[Card]) -> Vec<Card>
+    {
+        if self.tie_break == TieBreak::Positional
+        {
+            return cards.to_vec();
+        }
+
+        let mut card_count: HashMap<Card, usize> = HashMap::new();
+
+        for card in cards
+        {
+            *card_count.entry(*card).or_insert(0) += 1;
+        }
+
+        let mut ordered: Vec<Card> = cards.to_vec();
+        ordered.sort_by(|lhs, rhs| card_count[rhs].cmp(&card_count[lhs]).then(self.compare_card.compare_card(rhs, lhs)));
+        return ordered;
+    }
+
+    // First position, in tie-break order, where two hands of the same type hold different cards
+    fn deciding_cards(&self, lhs: &Hand, rhs: &Hand) -> Option<(usize, Card, Card)>
+    {
+        return self.tie_break_order(&lhs.cards).into_iter().zip(self.tie_break_order(&rhs.cards))
+            .enumerate()
+            .find(|(_, (lhs_card, rhs_card))| lhs_card != rhs_card)
+            .map(|(i, (lhs_card, rhs_card))| (i, lhs_card, rhs_card));
+    }
+
+    pub fn compare(&self, lhs: &Hand, rhs: &Hand) -> Ordering
+    {
+        if lhs.hand_type != rhs.hand_type
+        {
+            return lhs.hand_type.cmp(&rhs.hand_type);
+        }
+
+        return match self.deciding_cards(lhs, rhs)
+        {
+            Some((_, lhs_card, rhs_card)) => self.compare_card.compare_card(&lhs_card, &rhs_card),
+            None => Ordering::Equal
+        }
+    }
+
+    fn decided_by(&self, hand: &Hand, below: &Hand) -> String
+    {
+        if hand.hand_type != below.hand_type
+        {
+            return format!("{} > {}", self.find_type.kind(hand.hand_type).name, self.find_type.kind(below.hand_type).name);
+        }
+
+        return match self.deciding_cards(hand, below)
+        {
+            Some((i, card, below_card)) => format!("card {}: {} > {}", i + 1, card, below_card),
+            None => String::from("tied")
+        }
+    }
+
+    // Sorts the hands and explains each rank against the hand right below it
+    pub fn explain(&self, hands: &mut Vec<Hand>) -> Vec<Explanation>
+    {
+        hands.sort_by(|lhs, rhs| self.compare(lhs, rhs));
+
+        return hands.iter().enumerate().map(|(i, hand)| Explanation{
+            rank: i + 1,
+            cards: hand.cards.clone(),
+            substitution: self.find_type.substitute(&hand.cards, self.compare_card.as_ref()),
+            kind: self.find_type.kind(hand.hand_type).name.clone(),
+            bid: hand.bid,
+            decided_by: if i == 0 { String::from("-") } else { self.decided_by(hand, &hands[i - 1]) }
+        }).collect();
+    }
+
+    pub fn winnings(&self, hands: &mut Vec<Hand>) -> u64
+    {
+        hands.sort_by(|lhs, rhs| self.compare(lhs, rhs));
+
+        return hands.iter().enumerate().map(|(i, hand)| hand.bid * (i as u64 + 1)).sum();
+    }
+}
+
+pub fn render(explanations: &Vec<Explanation>) -> String
+{
+    let mut lines: Vec<String> = vec![format!("{:>5}  {:<8} {:<8} {:<10} {:>6} {:>10}  {}", "Rank", "Hand", "Becomes", "Type", "Bid", "Winnings", "Decided by")];
+
+    for explanation in explanations
+    {
+        lines.push(format!("{:>5}  {:<8} {:<8} {:<10} {:>6} {:>10}  {}",
+            explanation.rank,
+            explanation.cards.iter().collect::<String>(),
+            explanation.substitution.iter().collect::<String>(),
+            explanation.kind,
+            explanation.bid,
+            explanation.bid * explanation.rank as u64,
+            explanation.decided_by));
+    }
+
+    return lines.join("\n");
+}
@@ -1,267 +1,132 @@
-use std::{fs, collections::HashMap, cmp::Ordering, marker::PhantomData};
+mod engine;
 
-const FILE_PATH : &str = "input.txt";
-
-#[derive(Eq, PartialEq, PartialOrd, Ord, Clone, Copy, Hash)]
-enum Cards
-{
-    Joker = 1,
-    Two = 2,
-    Three = 3,
-    Four = 4,
-    Five = 5,
-    Six = 6,
-    Seven = 7,
-    Eight = 8,
-    Nine = 9,
-    T = 10,
-    J = 11,
-    Q = 12,
-    K = 13,
-    A = 14
-}
-
-fn convert_to_card(card_str: char) -> Cards
-{
-    return match card_str
-    {
-        '2' => Cards::Two,
-        '3' => Cards::Three,
-        '4' => Cards::Four,
-        '5' => Cards::Five,
-        '6' => Cards::Six,
-        '7' => Cards::Seven,
-        '8' => Cards::Eight,
-        '9' => Cards::Nine,
-        'T' => Cards::T,
-        'J' => Cards::J,
-        'Q' => Cards::Q,
-        'K' => Cards::K,
-        'A' => Cards::A,
-        _ => Cards::Joker,
-    };
-}
+use std::{fs, env};
 
-#[derive(PartialEq, PartialOrd, Eq, Ord)]
-enum HandType
-{
-    HighCard = 1,
-    OnePair = 2,
-    TwoPair = 3,
-    ThreeKind = 4,
-    FullHouse = 5,
-    FourKind = 6,
-    FiveKind = 7,
-}
+use engine::{HandKind, Pattern, TypeTable, RankOrder, Rules, TieBreak};
 
-fn to_type(first_card_count: u64, second_card_count: u64) -> HandType
-{
-    return match first_card_count
-    {
-        5 => HandType::FiveKind,
-        4 => HandType::FourKind,
-        3 => match second_card_count
-        {
-            2 => HandType::FullHouse,
-            _ => HandType::ThreeKind,
-        }
-        2 => match second_card_count
-        {
-            2 => HandType::TwoPair,
-            _ => HandType::OnePair,
-        }
-        _ => HandType::HighCard
-    }
-}
-
-struct Part1;
-
-struct Part2;
-
-trait FindType
-{
-    fn find_type(card_count: &mut HashMap<Cards, u64>) -> HandType;
-}
+const FILE_PATH : &str = "input.txt";
+const HAND_SIZE: usize = 5;
+const ORDER: &str = "23456789TJQKA";
+const JOKER_ORDER: &str = "J23456789TQKA";
+const JOKER: char = 'J';
 
-impl FindType for Part1
+fn camel_cards_types() -> Vec<HandKind>
 {
-    fn find_type(card_count: &mut HashMap<Cards, u64>) -> HandType
-    {
-        let mut counter: Vec<(&Cards, &u64)> = card_count.iter().collect();
-        counter.sort_by(|lhs, rhs| rhs.1.cmp(lhs.1));
+    let kinds: [(&str, Pattern); 7] = [
+        ("HighCard", vec![]),
+        ("OnePair", vec![2]),
+        ("TwoPair", vec![2, 2]),
+        ("ThreeKind", vec![3]),
+        ("FullHouse", vec![3, 2]),
+        ("FourKind", vec![4]),
+        ("FiveKind", vec![5])
+    ];
 
-        let first_card_count: u64 = if !counter.is_empty() { *counter[0].1 } else { 0 };
-        let second_card_count: u64 = if counter.len() > 1 { *counter[1].1 } else { 0 };
-        return to_type(first_card_count, second_card_count);
-    }
+    return kinds.into_iter().map(|(name, pattern)| HandKind{name: name.to_string(), pattern: pattern}).collect();
 }
 
-impl FindType for Part2
+fn camel_cards(jokers: bool) -> Rules
 {
-    fn find_type(card_count: &mut HashMap<Cards, u64>) -> HandType
-    {
-        let joker_count = card_count.remove(&Cards::J).unwrap_or(0);
-
-        let mut counter: Vec<(&Cards, &u64)> = card_count.iter().collect();
-        counter.sort_by(|lhs, rhs| rhs.1.cmp(lhs.1));
+    let (order, wildcards) = if jokers { (JOKER_ORDER, vec![JOKER]) } else { (ORDER, vec![]) };
 
-
-        let first_card_count: u64 = if !counter.is_empty() { *counter[0].1 } else { 0 };
-        let second_card_count: u64 = if counter.len() > 1 { *counter[1].1 } else { 0 };
-        return to_type(first_card_count + joker_count, second_card_count);
-    }
+    return Rules{
+        hand_size: HAND_SIZE,
+        find_type: Box::new(TypeTable::new(camel_cards_types(), wildcards)),
+        compare_card: Box::new(RankOrder::new(order)),
+        tie_break: TieBreak::Positional
+    };
 }
 
-#[derive(PartialEq, Eq)]
-struct SHand<TPart>
+fn total_winnings(rules: &Rules, contents: &str) -> u64
 {
-    cards: Vec<Cards>,
-    hand_type: HandType,
-    bid: u64,
-    part: PhantomData<TPart>
+    let mut hands = rules.parse_hands(contents).unwrap();
+    return rules.winnings(&mut hands);
 }
 
-trait CompareCard<TPart>
+fn part1(contents: &str) -> u64
 {
-    fn compare_card(lhs: &Cards, rhs: &Cards) -> Ordering;
+    return total_winnings(&camel_cards(false), contents);
 }
 
-impl<TPart> CompareCard<TPart> for Part1
+fn part2(contents: &str) -> u64
 {
-    fn compare_card(lhs: &Cards, rhs: &Cards) -> Ordering
-    {
-        return lhs.cmp(&rhs);
-    }
+    return total_winnings(&camel_cards(true), contents);
 }
 
-impl<TPart> CompareCard<TPart> for Part2
-{
-    fn compare_card(lhs: &Cards, rhs: &Cards) -> Ordering
-    {
-        let lhs_card = if lhs == &Cards::J { &Cards::Joker } else { &lhs };
-        let rhs_card = if rhs == &Cards::J { &Cards::Joker } else { &rhs };
-        return lhs_card.cmp(rhs_card);
-    }
-}
-
-trait CompareHand<TPart>
-{
-    fn compare(&self, rhs: &SHand<TPart>) -> Ordering;
-}
+const USAGE: &str = "Usage: <hand size> <order> <wildcards|-> <positional|rank> [types], e.g. \"5 23456789TJQKA J rank 2,2+2,3,3+2,4,5\"";
 
-impl<TPart: CompareCard<TPart>> CompareHand<TPart> for SHand<TPart>
+fn variant(contents: &str, args: &Vec<String>) -> Result<u64, String>
 {
-    fn compare(&self, rhs: &SHand<TPart>) -> Ordering
+    let (Some(hand_size), Some(order), Some(wildcards), Some(tie_break)) = (args.get(0), args.get(1), args.get(2), args.get(3)) else
     {
-        if self.hand_type < rhs.hand_type
-        {
-            return Ordering::Less;
-        }
-        else if self.hand_type > rhs.hand_type
-        {
-            return Ordering::Greater;
-        }
-
-        for i in 0..self.cards.len()
-        {
-            if self.cards[i] != rhs.cards[i]
-            {
-                return TPart::compare_card(&self.cards[i], &rhs.cards[i]);
-            }
-        }
-        
-        return Ordering::Equal;
-    }
-}
+        return Err(String::from(USAGE));
+    };
 
-impl <TPart: PartialOrd + CompareCard<TPart>> PartialOrd for SHand<TPart>
-{
-    fn partial_cmp(&self, rhs: &Self) -> Option<Ordering>
-    {
-        return Some(CompareHand::<TPart>::compare(self, rhs));
-    }
-}
+    let hand_size = hand_size.parse::<usize>().map_err(|_| String::from("Hand size should be a number"))?;
+    let wildcards: Vec<char> = if wildcards == "-" { vec![] } else { wildcards.chars().collect() };
 
-impl <TPart: Ord + CompareCard<TPart>> Ord for SHand<TPart>
-{
-    fn cmp(&self, rhs: &Self) -> Ordering
+    let tie_break = match tie_break.as_str()
     {
-        return CompareHand::<TPart>::compare(self, rhs)
-    }
-}
-
-fn calculate_hand_type<TPart: FindType>(cards: &Vec<Cards>) -> HandType
-{
-    let mut card_count: HashMap<Cards, u64> = HashMap::new();
+        "positional" => TieBreak::Positional,
+        "rank" => TieBreak::RankBased,
+        _ => return Err(String::from("Tie break should be one of positional or rank"))
+    };
 
-    for card in cards
+    let kinds = match args.get(4)
     {
-        *card_count.entry(card.clone()).or_insert(0) += 1;
-    }
-
-    return TPart::find_type(&mut card_count);
-}
+        Some(types) => engine::parse_types(types)?,
+        None => camel_cards_types()
+    };
 
-fn parse_hand<TPart: FindType>(line: &str) -> SHand<TPart>
-{
-    let (hand_str, bid_str) = line.split_once(" ").unwrap().try_into().unwrap();
-    let cards = hand_str.chars().filter_map(|c| Some(convert_to_card(c))).collect::<Vec<Cards>>();
-    let bid: u64 = bid_str.parse::<u64>().unwrap();
-    let hand_type = calculate_hand_type::<TPart>(&cards);
+    let rules = Rules{
+        hand_size: hand_size,
+        find_type: Box::new(TypeTable::new(kinds, wildcards)),
+        compare_card: Box::new(RankOrder::new(order)),
+        tie_break: tie_break
+    };
 
-    return SHand { cards: cards, hand_type: hand_type, bid: bid, part: PhantomData };
+    return explain(&rules, contents);
 }
 
-fn parse_hands<TPart: FindType>(contents: &str) -> Vec<SHand<TPart>>
+// Prints every hand with its wildcard substitution, final rank and what put it above the previous hand
+fn explain(rules: &Rules, contents: &str) -> Result<u64, String>
 {
-    let mut hands = Vec::new();
+    let mut hands = rules.parse_hands(contents)?;
+    let explanations = rules.explain(&mut hands);
 
-    for line in contents.lines()
-    {
-        hands.push(parse_hand(line));
-    }
+    println!("{}", engine::render(&explanations));
 
-    return hands;
+    return Ok(explanations.iter().map(|explanation| explanation.bid * explanation.rank as u64).sum());
 }
 
-fn part1<TPart: FindType + CompareCard<TPart>>(contents: &str) -> u64
+fn main()
 {
-    let mut result: u64 = 0;
-    let mut hands = parse_hands::<Part1>(&contents);
+    let contents: String = fs::read_to_string(FILE_PATH)
+        .expect("Should have been able to read the file");
 
-    hands.sort_by(|lhs, rhs| lhs.compare(rhs));
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    for (i, hand) in hands.iter().enumerate()
+    if !args.is_empty()
     {
-        result += hand.bid * (i as u64 + 1);
-    }
+        // Usage: explain [part1], or the custom rules described on variant
+        let result = if args[0] == "explain" { explain(&camel_cards(args.get(1).map(|part| part.as_str()) != Some("part1")), &contents) } else { variant(&contents, &args) };
 
-    return result;
-}
-
-fn part2<TPart: FindType + CompareCard<TPart>>(contents: &str) -> u64
-{
-    let mut result: u64 = 0;
-    let mut hands = parse_hands::<Part2>(&contents);
-
-    hands.sort_by(|lhs, rhs| lhs.compare(rhs));
+        match result
+        {
+            Ok(result) => println!("{}", result),
+            Err(message) =>
+            {
+                eprintln!("{}", message);
+                std::process::exit(1);
+            }
+        }
 
-    for (i, hand) in hands.iter().enumerate()
-    {
-        result += hand.bid * (i as u64 + 1);
+        return;
     }
 
-    return result;
-}
+    let part1_result: u64 = part1(&contents);
+    let part2_result: u64 = part2(&contents);
 
-fn main()
-{
-    let contents: String = fs::read_to_string(FILE_PATH)
-        .expect("Should have been able to read the file");
-
-    let part1_result: u64 = part1::<Part1>(&contents);
-    let part2_result: u64 = part2::<Part2>(&contents);
-    
     println!("{}", part1_result);
     println!("{}", part2_result);
-}
\ No newline at end of file
+}
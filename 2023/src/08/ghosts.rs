@@ -0,0 +1,149 @@
+use std::{collections::HashMap, hash::Hash};
+
+use ::num::integer::{lcm, Integer};
+
+// Walk of a single ghost over (node, instruction index) states. Every state repeats after
+// `prefix` steps with period `length`; end nodes are hit at `prefix_hits` before the loop
+// and at `cycle_hits` (absolute times within the first lap) once in it
+pub struct Ghost
+{
+    pub prefix: u64,
+    pub length: u64,
+    pub prefix_hits: Vec<u64>,
+    pub cycle_hits: Vec<u64>
+}
+
+pub struct Analysis
+{
+    pub arrival: Option<u64>,
+    pub shortcut: Option<u64>,
+    pub shortcut_valid: bool
+}
+
+pub fn analyze<TNode, TStep, TIsEnd>(start: TNode, period: usize, step: TStep, is_end: TIsEnd) -> Ghost
+    where
+        TNode: Hash + Eq + Clone,
+        TStep: Fn(&TNode, usize) -> TNode,
+        TIsEnd: Fn(&TNode) -> bool
+{
+    let mut seen: HashMap<(TNode, usize), u64> = HashMap::new();
+    let mut hits: Vec<u64> = Vec::new();
+    let mut node: TNode = start;
+    let mut time: u64 = 0;
+
+    loop
+    {
+        let index = time as usize % period;
+        let seen_option = seen.get(&(node.clone(), index));
+
+        if seen_option.is_some()
+        {
+            let prefix = *seen_option.unwrap();
+            let (prefix_hits, cycle_hits): (Vec<u64>, Vec<u64>) = hits.into_iter().partition(|&hit| hit < prefix);
+
+            return Ghost{prefix: prefix, length: time - prefix, prefix_hits: prefix_hits, cycle_hits: cycle_hits};
+        }
+
+        seen.insert((node.clone(), index), time);
+
+        if is_end(&node)
+        {
+            hits.push(time);
+        }
+
+        node = step(&node, index);
+        time += 1;
+    }
+}
+
+impl Ghost
+{
+    pub fn first_hit(&self) -> Option<u64>
+    {
+        return self.prefix_hits.first().or(self.cycle_hits.first()).copied();
+    }
+
+    pub fn is_hit(&self, time: u64) -> bool
+    {
+        if time < self.prefix
+        {
+            return self.prefix_hits.contains(&time);
+        }
+
+        return self.cycle_hits.iter().any(|hit| (time - self.prefix) % self.length == hit - self.prefix);
+    }
+
+    // The LCM shortcut assumes the ghost is at an end exactly at the multiples of its first arrival
+    pub fn hits_multiples_of_first(&self) -> bool
+    {
+        let first_option = self.first_hit();
+
+        if first_option.is_none() || first_option.unwrap() == 0
+        {
+            return false;
+        }
+
+        let first = first_option.unwrap();
+        let hits: Vec<u64> = self.prefix_hits.iter().chain(self.cycle_hits.iter()).copied().collect();
+        let multiples: Vec<u64> = (1..).map(|k| k * first).take_while(|&time| time < self.prefix + self.length).collect();
+
+        return self.length % first == 0 && hits == multiples;
+    }
+}
+
+// Merges x = lhs.0 (mod lhs.1) with x = rhs.0 (mod rhs.1), for moduli that need not be coprime
+fn combine(lhs: (i128, i128), rhs: (i128, i128)) -> Option<(i128, i128)>
+{
+    let (lhs_remainder, lhs_modulus) = lhs;
+    let (rhs_remainder, rhs_modulus) = rhs;
+    let gcd = lhs_modulus.extended_gcd(&rhs_modulus);
+
+    if (rhs_remainder - lhs_remainder) % gcd.gcd != 0
+    {
+        return None;
+    }
+
+    let modulus = lhs_modulus / gcd.gcd * rhs_modulus;
+    let factor = ((rhs_remainder - lhs_remainder) / gcd.gcd * gcd.x).mod_floor(&(rhs_modulus / gcd.gcd));
+
+    return Some(((lhs_remainder + lhs_modulus * factor).mod_floor(&modulus), modulus));
+}
+
+// Earliest time at or after every prefix where all ghosts sit on a cycle hit, trying each combination of hits
+fn solve_cycles(ghosts: &Vec<Ghost>) -> Option<u64>
+{
+    let bound = ghosts.iter().map(|ghost| ghost.prefix).max().unwrap_or(0) as i128;
+    let mut congruences: Vec<(i128, i128)> = vec![(0, 1)];
+
+    for ghost in ghosts
+    {
+        congruences = congruences.iter()
+            .flat_map(|&congruence| ghost.cycle_hits.iter().filter_map(move |&hit| combine(congruence, (hit as i128, ghost.length as i128))))
+            .collect();
+    }
+
+    return congruences.iter()
+        .map(|&(remainder, modulus)| if remainder >= bound { remainder } else { remainder + (bound - remainder + modulus - 1) / modulus * modulus })
+        .min()
+        .map(|time| time as u64);
+}
+
+pub fn solve(ghosts: &Vec<Ghost>) -> Analysis
+{
+    // Hits before a ghost loops can only be shared at those exact times
+    let early = ghosts.iter()
+        .flat_map(|ghost| ghost.prefix_hits.iter().copied())
+        .filter(|&time| ghosts.iter().all(|ghost| ghost.is_hit(time)))
+        .min();
+
+    let arrival = match (early, solve_cycles(ghosts))
+    {
+        (Some(lhs), Some(rhs)) => Some(lhs.min(rhs)),
+        (lhs, rhs) => lhs.or(rhs)
+    };
+
+    let firsts: Option<Vec<u64>> = ghosts.iter().map(|ghost| ghost.first_hit()).collect();
+    let shortcut = firsts.map(|firsts| firsts.into_iter().fold(1, |lhs, rhs| lcm(lhs, rhs)));
+
+    return Analysis{arrival: arrival, shortcut: shortcut, shortcut_valid: shortcut.is_some() && shortcut == arrival};
+}
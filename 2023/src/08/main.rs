@@ -1,10 +1,14 @@
 // Used LCM idea from https://www.reddit.com/r/adventofcode/comments/18df7px/2023_day_8_solutions/
 
-use std::{fs, collections::HashMap};
-use ::num::integer::lcm;
+mod ghosts;
+
+use std::{fs, env, collections::HashMap};
+
+use ghosts::Ghost;
 
 const FILE_PATH : &str = "input.txt";
 
+#[derive(Clone, Copy)]
 enum Direction
 {
     Left,
@@ -22,35 +26,92 @@ impl Direction
     }
 }
 
-type Pos = String;
-struct Choice
+// Node names are interned into dense ids, so walking only indexes into the successor arrays
+type Node = u32;
+
+struct Map
 {
-    left: Pos,
-    right: Pos
+    names: Vec<String>,
+    ids: HashMap<String, Node>,
+    left: Vec<Node>,
+    right: Vec<Node>
 }
 
-trait Get
+// One bit per node id
+struct NodeSet
 {
-    fn get(&self, direction: Direction) -> &Pos;
+    words: Vec<u64>
+}
+
+impl NodeSet
+{
+    pub fn contains(&self, node: Node) -> bool
+    {
+        return (self.words[node as usize / 64] >> (node % 64)) & 1 == 1;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Node> + '_
+    {
+        return (0..(self.words.len() * 64) as Node).filter(|&node| self.contains(node));
+    }
 }
 
-impl Get for Choice
+impl Map
 {
-    fn get(&self, direction: Direction) -> &Pos
+    fn intern(&mut self, name: &str) -> Node
+    {
+        if let Some(&node) = self.ids.get(name)
+        {
+            return node;
+        }
+
+        let node = self.names.len() as Node;
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), node);
+        self.left.push(node);
+        self.right.push(node);
+
+        return node;
+    }
+
+    pub fn id(&self, name: &str) -> Option<Node>
+    {
+        return self.ids.get(name).copied();
+    }
+
+    pub fn name(&self, node: Node) -> &str
+    {
+        return &self.names[node as usize];
+    }
+
+    pub fn next(&self, node: Node, direction: Direction) -> Node
     {
         return match direction
         {
-            Direction::Left => &self.left,
-            Direction::Right => &self.right
+            Direction::Left => self.left[node as usize],
+            Direction::Right => self.right[node as usize]
         }
     }
-}
 
-type Map = HashMap<Pos, Choice>;
+    pub fn select<TPredicate: Fn(&str) -> bool>(&self, predicate: TPredicate) -> NodeSet
+    {
+        let mut words: Vec<u64> = vec![0; self.names.len().div_ceil(64)];
+
+        for (node, name) in self.names.iter().enumerate()
+        {
+            if predicate(name)
+            {
+                words[node / 64] |= 1 << (node % 64);
+            }
+        }
+
+        return NodeSet{words: words};
+    }
+}
 
 struct Path
 {
-    current_pos: String
+    current_pos: Node
 }
 
 struct Part1;
@@ -58,57 +119,65 @@ struct Part2;
 
 trait IsEnd
 {
-    fn is_end(path: &Path) -> bool;
+    fn is_end(name: &str) -> bool;
 }
 
 impl IsEnd for Part1
 {
-    fn is_end(path: &Path) -> bool
+    fn is_end(name: &str) -> bool
     {
-        return path.current_pos == "ZZZ";
+        return name == "ZZZ";
     }
 }
 
 impl IsEnd for Part2
 {
-    fn is_end(path: &Path) -> bool
+    fn is_end(name: &str) -> bool
     {
-        return path.current_pos.ends_with("Z");
+        return name.ends_with("Z");
     }
 }
 
 fn create_map(contents: &String) -> Map
 {
-    let mut map : Map = HashMap::new();
+    let mut map : Map = Map{names: Vec::new(), ids: HashMap::new(), left: Vec::new(), right: Vec::new()};
+    let mut defined: Vec<bool> = Vec::new();
 
     for line in contents.lines().skip(2)
     {
         let position_split: Vec<&str> = line.split(" = ").collect();
         let left_right_split: Vec<&str> = position_split.last().unwrap().split(", ").collect();
         
-        let start_position = position_split.first().unwrap();
-        let left = left_right_split.first().unwrap().replace("(", "");
-        let right = left_right_split.last().unwrap().replace(")", "");
-        map.insert(start_position.to_string(), Choice { left: left, right: right });
+        let start_position = map.intern(position_split.first().unwrap());
+        let left = map.intern(&left_right_split.first().unwrap().replace("(", ""));
+        let right = map.intern(&left_right_split.last().unwrap().replace(")", ""));
+
+        map.left[start_position as usize] = left;
+        map.right[start_position as usize] = right;
+
+        defined.resize(map.names.len(), false);
+        defined[start_position as usize] = true;
     }
 
+    let undefined_option = defined.iter().position(|is_defined| !is_defined);
+    assert!(undefined_option.is_none(), "Node {} is never defined", map.name(undefined_option.unwrap_or(0) as Node));
+
     return map;
 }
 
-fn run<TPart: IsEnd>(path: &mut Path, map: &Map, instructions: &Vec<char>) -> u64
+fn parse_instructions(contents: &String) -> Vec<Direction>
+{
+    return contents.lines().next().unwrap().chars().map(|c| Direction::from(c).expect("Instructions should be L or R")).collect();
+}
+
+fn run(path: &mut Path, map: &Map, instructions: &Vec<Direction>, ends: &NodeSet) -> u64
 {
     let mut result = 0;
     let mut cur_index = 0;
 
-    loop
+    while !ends.contains(path.current_pos)
     {
-        if TPart::is_end(&path)
-        {
-            break;
-        }
-
-        let direction: Direction = Direction::from(instructions[cur_index]).unwrap();
-        path.current_pos = map.get(&path.current_pos).unwrap().get(direction).to_string();
+        path.current_pos = map.next(path.current_pos, instructions[cur_index]);
 
         cur_index = (cur_index + 1) % instructions.len();
         result += 1;
@@ -117,25 +186,59 @@ fn run<TPart: IsEnd>(path: &mut Path, map: &Map, instructions: &Vec<char>) -> u6
     return result;
 }
 
-fn part1(instructions: &Vec<char>, map: &Map) -> u64
+fn part1(instructions: &Vec<Direction>, map: &Map) -> u64
 {
-    let mut path: Path = Path { current_pos: "AAA".to_string()};
-    return run::<Part1>(&mut path, &map, &instructions);
+    let mut path: Path = Path { current_pos: map.id("AAA").expect("Map should have an AAA node")};
+    return run(&mut path, &map, &instructions, &map.select(Part1::is_end));
 }
 
-fn part2(instructions: &Vec<char>, map: &Map) -> u64
+fn analyze_ghosts(instructions: &Vec<Direction>, map: &Map) -> Vec<(String, Ghost)>
 {
-    let mut steps: Vec<u64> = Vec::new();
-    for pos in map.keys()
+    let starts: NodeSet = map.select(|name| name.ends_with('A'));
+    let ends: NodeSet = map.select(Part2::is_end);
+
+    let mut names: Vec<&str> = starts.iter().map(|node| map.name(node)).collect();
+    names.sort();
+
+    let step = |node: &Node, index: usize| map.next(*node, instructions[index]);
+    let is_end = |node: &Node| ends.contains(*node);
+
+    return names.into_iter().map(|name| (name.to_string(), ghosts::analyze(map.id(name).unwrap(), instructions.len(), step, is_end))).collect();
+}
+
+fn part2(instructions: &Vec<Direction>, map: &Map) -> Result<u64, String>
+{
+    let ghosts: Vec<Ghost> = analyze_ghosts(instructions, map).into_iter().map(|(_, ghost)| ghost).collect();
+    return ghosts::solve(&ghosts).arrival.ok_or(String::from("The ghosts never stand on end nodes at the same time"));
+}
+
+// Per ghost prefix, cycle length and end hits, and whether the LCM of first arrivals gives the right answer
+fn report(instructions: &Vec<Direction>, map: &Map)
+{
+    let analyzed = analyze_ghosts(instructions, map);
+
+    for (start, ghost) in &analyzed
     {
-        if pos.ends_with('A')
-        {
-            let mut path: Path = Path { current_pos: pos.clone()};
-            steps.push(run::<Part2>(&mut path, &map, &instructions));
-        }
+        println!("{}: prefix {}, cycle length {}, hits before cycle {:?}, hits in cycle {:?}{}",
+            start, ghost.prefix, ghost.length, ghost.prefix_hits, ghost.cycle_hits,
+            if ghost.hits_multiples_of_first() { "" } else { " (not only at multiples of its first arrival)" });
+    }
+
+    let ghosts: Vec<Ghost> = analyzed.into_iter().map(|(_, ghost)| ghost).collect();
+    let analysis = ghosts::solve(&ghosts);
+
+    match analysis.arrival
+    {
+        Some(arrival) => println!("All ghosts arrive after {} steps", arrival),
+        None => println!("The ghosts never stand on end nodes at the same time")
     }
 
-    return steps.into_iter().reduce(|lhs, rhs| lcm(lhs, rhs)).unwrap();
+    match (analysis.shortcut, analysis.shortcut_valid)
+    {
+        (Some(shortcut), true) => println!("LCM shortcut is valid: {}", shortcut),
+        (Some(shortcut), false) => println!("LCM shortcut is INVALID: it gives {}", shortcut),
+        (None, _) => println!("LCM shortcut is INVALID: some ghost never reaches an end")
+    }
 }
 
 fn main()
@@ -143,12 +246,28 @@ fn main()
     let contents: String = fs::read_to_string(FILE_PATH)
         .expect("Should have been able to read the file");
 
-    let instructions: Vec<char> = contents.lines().take(1).collect::<String>().chars().collect();
-    let map : HashMap<Pos, Choice> = create_map(&contents);
+    let instructions: Vec<Direction> = parse_instructions(&contents);
+    let map : Map = create_map(&contents);
+
+    // Usage: analyze, to check each ghost's cycle instead of only printing the answers
+    if env::args().nth(1).as_deref() == Some("analyze")
+    {
+        report(&instructions, &map);
+        return;
+    }
 
     let part1_result: u64 = part1(&instructions, &map);
-    let part2_result: u64 = part2(&instructions, &map);
-    
+    let part2_result: Result<u64, String> = part2(&instructions, &map);
+
     println!("{}", part1_result);
-    println!("{}", part2_result);
+
+    match part2_result
+    {
+        Ok(result) => println!("{}", result),
+        Err(message) =>
+        {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    }
 }
\ No newline at end of file
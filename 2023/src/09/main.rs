@@ -1,89 +1,111 @@
-use std::{fs, collections::VecDeque};
+mod sequence;
+
+use std::{fs, env};
+
+use num::{BigInt, BigRational};
+
+use sequence::{Sequence, Value};
 
 const FILE_PATH : &str = "input.txt";
 
-type TSteps = VecDeque<i64>;
-type TValues = Vec<TSteps>;
-type THistory = Vec<TValues>;
+type THistory = Vec<Vec<Value>>;
 
 fn parse(contents: &String) -> THistory
 {
     let mut history: THistory = THistory::new();
+
     for line in contents.lines()
     {
-        history.push(TValues::new());
-
-        let values: TSteps = line.split(" ").map(|s| s.parse::<i64>().unwrap()).collect();
-        history.last_mut().unwrap().push(values);
+        history.push(line.split_whitespace().map(|s| s.parse::<Value>().unwrap()).collect());
     }
 
     return history;
 }
 
-fn calculate(history: &mut THistory)
+fn calculate(history: &THistory) -> Result<Vec<Sequence>, String>
+{
+    return history.iter().map(|values| Sequence::new(values)).collect();
+}
+
+fn part1(sequences: &Vec<Sequence>) -> BigInt
+{
+    return sequences.iter().map(|sequence| {
+        let next = sequence.next(1).remove(0);
+        // The closed form has to agree with the difference table
+        debug_assert_eq!(sequence.polynomial().evaluate(sequence.len() as Value), BigRational::from_integer(next.clone()));
+        next
+    }).sum();
+}
+
+fn part2(sequences: &Vec<Sequence>) -> BigInt
+{
+    return sequences.iter().map(|sequence| sequence.previous(1).remove(0)).sum();
+}
+
+const USAGE: &str = "Usage: polynomial, or extrapolate <steps> to print that many values on either side of each sequence";
+
+fn explore(sequences: &Vec<Sequence>, args: &Vec<String>) -> Result<(), String>
 {
-    for values in history
+    match args[0].as_str()
     {
-        let mut previous_step = values.last().unwrap();
-        while !previous_step.iter().all(|elem| elem == &0)
+        "polynomial" =>
         {
-            let mut new_step: TSteps = TSteps::new();
-            
-            for i in 1..previous_step.len()
+            for sequence in sequences
             {
-                new_step.push_back(previous_step[i] - previous_step[i - 1]);
+                println!("degree {}: {}", sequence.degree(), sequence.polynomial());
             }
-            
-            values.push(new_step);
-            previous_step = values.last().unwrap();
         }
-
-        for row in (0..values.len() - 1).rev()
+        "extrapolate" =>
         {
-            let next: i64 = *values[row].back().unwrap() + *values[row + 1].back().unwrap();
-            let previous: i64 = *values[row].front().unwrap() - *values[row + 1].front().unwrap();
+            let steps = args.get(1).ok_or(String::from("Extrapolate needs a number of steps"))?
+                .parse::<usize>().map_err(|_| String::from("Steps should be a number"))?;
 
-            values[row].push_back(next);
-            values[row].push_front(previous);
+            for sequence in sequences
+            {
+                let previous: Vec<String> = sequence.previous(steps).iter().rev().map(|value| value.to_string()).collect();
+                let next: Vec<String> = sequence.next(steps).iter().map(|value| value.to_string()).collect();
+                println!("{} | {}", previous.join(" "), next.join(" "));
+            }
         }
+        _ => return Err(String::from("Mode should be one of polynomial or extrapolate"))
     }
+
+    return Ok(());
 }
 
-fn part1(history: &THistory) -> i64
+fn main()
 {
-    let mut result = 0;
+    let contents: String = fs::read_to_string(FILE_PATH)
+        .expect("Should have been able to read the file");
 
-    for values in history
-    {
-        result += values.first().unwrap().back().unwrap();
-    }
+    let history: THistory = parse(&contents);
 
-    return result;
-}
+    let sequences = match calculate(&history)
+    {
+        Ok(sequences) => sequences,
+        Err(message) =>
+        {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
 
-fn part2(history: &THistory) -> i64
-{
-    let mut result = 0;
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    for values in history
+    if !args.is_empty()
     {
-        result += values.first().unwrap().front().unwrap();
-    }
-
-    return result;
-}
+        if let Err(message) = explore(&sequences, &args)
+        {
+            eprintln!("{}\n{}", message, USAGE);
+            std::process::exit(1);
+        }
 
-fn main()
-{
-    let contents: String = fs::read_to_string(FILE_PATH)
-        .expect("Should have been able to read the file");
+        return;
+    }
 
-    let mut history: THistory = parse(&contents);
-    calculate(&mut history);
+    let part1_result: BigInt = part1(&sequences);
+    let part2_result: BigInt = part2(&sequences);
 
-    let part1_result: i64 = part1(&history);
-    let part2_result: i64 = part2(&history);
-    
     println!("{}", part1_result);
     println!("{}", part2_result);
-}
\ No newline at end of file
+}
@@ -0,0 +1,172 @@
+use std::fmt::Display;
+
+use num::{BigInt, BigRational, Zero, One, Signed};
+
+pub type Value = i128;
+
+// A sequence generated by a polynomial, kept in Newton forward difference form:
+// f(x) = sum over k of differences[k] * C(x, k), with x = 0 at the first value.
+// Differences and extrapolated values are big integers, since they outgrow the input quickly
+pub struct Sequence
+{
+    length: usize,
+    differences: Vec<BigInt>
+}
+
+// Closed form with the coefficient of x^k at index k, where x = 0 is the first value
+pub struct Polynomial
+{
+    coefficients: Vec<BigRational>
+}
+
+// Generalised binomial coefficient, also defined for negative x. Every partial product is itself a binomial so the division is exact
+fn binomial(x: &BigInt, k: usize) -> BigInt
+{
+    let mut result: BigInt = BigInt::one();
+
+    for j in 0..k
+    {
+        result = result * (x - j) / (j + 1);
+    }
+
+    return result;
+}
+
+impl Sequence
+{
+    pub fn new(values: &[Value]) -> Result<Sequence, String>
+    {
+        let mut row: Vec<BigInt> = values.iter().map(|value| BigInt::from(*value)).collect();
+        let mut differences: Vec<BigInt> = Vec::new();
+
+        while !row.iter().all(|value| value.is_zero())
+        {
+            if row.len() == 1
+            {
+                return Err(format!("Sequence {:?} never reaches all zeros, it needs more than {} values to pin down", values, values.len()));
+            }
+
+            differences.push(row[0].clone());
+            row = row.windows(2).map(|pair| &pair[1] - &pair[0]).collect();
+        }
+
+        return Ok(Sequence{length: values.len(), differences: differences});
+    }
+
+    pub fn len(&self) -> usize
+    {
+        return self.length;
+    }
+
+    pub fn degree(&self) -> usize
+    {
+        return self.differences.len().saturating_sub(1);
+    }
+
+    pub fn at(&self, x: Value) -> BigInt
+    {
+        let x = BigInt::from(x);
+        return self.differences.iter().enumerate().map(|(k, difference)| difference * binomial(&x, k)).sum();
+    }
+
+    // The k values after the last one, in order
+    pub fn next(&self, k: usize) -> Vec<BigInt>
+    {
+        return (0..k).map(|i| self.at((self.length + i) as Value)).collect();
+    }
+
+    // The k values before the first one, nearest first
+    pub fn previous(&self, k: usize) -> Vec<BigInt>
+    {
+        return (1..=k).map(|i| self.at(-(i as Value))).collect();
+    }
+
+    pub fn polynomial(&self) -> Polynomial
+    {
+        let mut coefficients: Vec<BigRational> = vec![BigRational::zero()];
+        // Coefficients of C(x, k), built up one factor (x - k) / (k + 1) at a time
+        let mut basis: Vec<BigRational> = vec![BigRational::one()];
+
+        for (k, difference) in self.differences.iter().enumerate()
+        {
+            let factor = BigRational::from_integer(difference.clone());
+
+            coefficients.resize(basis.len(), BigRational::zero());
+
+            for (i, coefficient) in basis.iter().enumerate()
+            {
+                coefficients[i] += &factor * coefficient;
+            }
+
+            let shift = BigRational::from_integer(BigInt::from(k));
+            let divisor = BigRational::from_integer(BigInt::from(k + 1));
+            let mut next_basis: Vec<BigRational> = vec![BigRational::zero(); basis.len() + 1];
+
+            for (i, coefficient) in basis.iter().enumerate()
+            {
+                next_basis[i + 1] += coefficient / &divisor;
+                next_basis[i] -= coefficient * &shift / &divisor;
+            }
+
+            basis = next_basis;
+        }
+
+        while coefficients.len() > 1 && coefficients.last().unwrap().is_zero()
+        {
+            coefficients.pop();
+        }
+
+        return Polynomial{coefficients: coefficients};
+    }
+}
+
+impl Polynomial
+{
+    pub fn evaluate(&self, x: Value) -> BigRational
+    {
+        let x = BigRational::from_integer(BigInt::from(x));
+
+        return self.coefficients.iter().rev().fold(BigRational::zero(), |result, coefficient| result * &x + coefficient);
+    }
+}
+
+impl Display for Polynomial
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        let terms: Vec<(usize, &BigRational)> = self.coefficients.iter().enumerate().rev().filter(|(_, coefficient)| !coefficient.is_zero()).collect();
+
+        if terms.is_empty()
+        {
+            return write!(f, "0");
+        }
+
+        for (i, (power, coefficient)) in terms.iter().enumerate()
+        {
+            let sign = if coefficient.is_negative() { "-" } else { "+" };
+
+            if i == 0
+            {
+                write!(f, "{}", if coefficient.is_negative() { "-" } else { "" })?;
+            }
+            else
+            {
+                write!(f, " {} ", sign)?;
+            }
+
+            let magnitude = coefficient.abs();
+            let magnitude = if magnitude.is_integer() { magnitude.to_string() } else { format!("({})", magnitude) };
+
+            match (*power, coefficient.abs().is_one())
+            {
+                (0, _) => write!(f, "{}", magnitude)?,
+                (1, true) => write!(f, "x")?,
+                (1, false) => write!(f, "{}x", magnitude)?,
+                (_, true) => write!(f, "x^{}", power)?,
+                (_, false) => write!(f, "{}x^{}", magnitude, power)?
+            }
+        }
+
+        return Ok(());
+    }
+}
@@ -1,13 +1,14 @@
 // Used idea from https://www.reddit.com/r/adventofcode/comments/18f1sgh/comment/kcr8tyf/ for part 2
 
-use std::{fs, ops::{Add, AddAssign}};
+use std::{fs, env, ops::{Add, AddAssign}};
 
-use num::abs;
+use code_advent_2023::polygon::Polygon;
 
 const FILE_PATH : &str = "input.txt";
 
 type Vertices = Vec<Position>;
 type World = Vec<Vec<char>>;
+type Tiles = Vec<Vec<Tile>>;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 struct Position
@@ -26,6 +27,14 @@ enum Direction
     None
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tile
+{
+    Loop,
+    Inside,
+    Outside
+}
+
 impl Add for Position
 {
     type Output = Self;
@@ -100,6 +109,60 @@ fn get_next_direction(previous_direction: Direction, pipe: char) -> Direction
     }
 }
 
+fn to_box_drawing(pipe: char) -> char
+{
+    return match pipe
+    {
+        '|' => '│',
+        '-' => '─',
+        'L' => '└',
+        'J' => '┘',
+        '7' => '┐',
+        'F' => '┌',
+        _ => pipe
+    }
+}
+
+fn opposite(direction: Direction) -> Direction
+{
+    return match direction
+    {
+        Direction::Left => Direction::Right,
+        Direction::Right => Direction::Left,
+        Direction::Up => Direction::Down,
+        Direction::Down => Direction::Up,
+        Direction::None => Direction::None
+    }
+}
+
+fn get_connections(pipe: char) -> Vec<Direction>
+{
+    return match pipe
+    {
+        '|' => vec![Direction::Up, Direction::Down],
+        '-' => vec![Direction::Left, Direction::Right],
+        'L' => vec![Direction::Up, Direction::Right],
+        'J' => vec![Direction::Up, Direction::Left],
+        '7' => vec![Direction::Down, Direction::Left],
+        'F' => vec![Direction::Down, Direction::Right],
+        _ => vec![]
+    }
+}
+
+fn get_pipe(first: Direction, second: Direction) -> char
+{
+    return match (first, second)
+    {
+        (Direction::Up, Direction::Down) | (Direction::Down, Direction::Up) => '|',
+        (Direction::Left, Direction::Right) | (Direction::Right, Direction::Left) => '-',
+        (Direction::Up, Direction::Right) | (Direction::Right, Direction::Up) => 'L',
+        (Direction::Up, Direction::Left) | (Direction::Left, Direction::Up) => 'J',
+        (Direction::Down, Direction::Left) | (Direction::Left, Direction::Down) => '7',
+        (Direction::Down, Direction::Right) | (Direction::Right, Direction::Down) => 'F',
+        _ => '.'
+    }
+}
+
 fn parse_world(contents: &String) -> World
 {
     let mut world: World = World::new();
@@ -112,85 +175,151 @@ fn parse_world(contents: &String) -> World
     return world;
 }
 
-fn find_start(world: &World) -> Position
+fn find_start(world: &World) -> Result<Position, String>
 {
+    let mut starts: Vertices = Vertices::new();
+
     for (i, line) in world.iter().enumerate()
     {
         for (j, character) in line.iter().enumerate()
         {
             if *character == 'S'
             {
-                return Position{x: j as i64, y: i as i64};
+                starts.push(Position{x: j as i64, y: i as i64});
             }
         }
     }
 
-    return Position { x: 0, y: 0 }
+    return match starts.len()
+    {
+        1 => Ok(starts[0]),
+        0 => Err(String::from("there is no start tile")),
+        count => Err(format!("there are {} start tiles", count))
+    }
 }
 
-fn is_position_valid(position: &Position, max_position: &Position) -> bool
+fn get_tile(world: &World, position: &Position) -> Option<char>
 {
-    return (position.x >= 0 && position.x <= max_position.x)
-        && (position.y >= 0 && position.y <= max_position.y);
+    if position.x < 0 || position.y < 0
+    {
+        return None;
+    }
+
+    return world.get(position.y as usize).and_then(|row| row.get(position.x as usize)).copied();
 }
 
-fn get_possible_start_adjacent(world: &World, start: &Position, max_position: &Position) -> Direction
+// The pipe under the start is the one joining the two neighbours that connect back to it
+fn resolve_start(world: &mut World, start: &Position) -> Result<(), String>
 {
     let directions = [Direction::Left, Direction::Right, Direction::Up, Direction::Down];
+    let mut connected: Vec<Direction> = Vec::new();
+
     for direction in directions
     {
-        let new_position = *start + convert_to_coordinates(direction);
+        let neighbor = get_tile(world, &(*start + convert_to_coordinates(direction)));
 
-        if is_position_valid(&new_position, max_position)
-            && get_next_direction(direction, world[new_position.y as usize][new_position.x as usize]) != Direction::None
+        if neighbor.is_some_and(|pipe| get_connections(pipe).contains(&opposite(direction)))
         {
-            return direction;
+            connected.push(direction);
         }
     }
 
-    return Direction::None;
+    if connected.len() != 2
+    {
+        return Err(format!("the start ({}, {}) connects to {} neighbours instead of 2", start.x, start.y, connected.len()));
+    }
+
+    world[start.y as usize][start.x as usize] = get_pipe(connected[0], connected[1]);
+    return Ok(());
 }
 
-fn calculate_vertices(world: &World, start: &Position, max_position: &Position) -> Vertices
+fn calculate_vertices(world: &World, start: &Position) -> Result<Vertices, String>
 {
+    let tile_count: usize = world.iter().map(|row| row.len()).sum();
     let mut vertices: Vertices = vec![start.clone()];
 
-    let mut current_direction = get_possible_start_adjacent(&world, start, max_position);
+    let mut current_direction = get_connections(get_tile(world, start).unwrap())[0];
     let mut current_position = *start + convert_to_coordinates(current_direction);
 
     while current_position != *start
     {
+        let tile_option = get_tile(world, &current_position);
+
+        if tile_option.is_none()
+        {
+            return Err(format!("the loop walks off the map at ({}, {})", current_position.x, current_position.y));
+        }
+
+        if vertices.len() > tile_count
+        {
+            return Err(String::from("the loop never returns to the start"));
+        }
+
         vertices.push(current_position);
 
-        current_direction = get_next_direction(current_direction, world[current_position.y as usize][current_position.x as usize]);
+        let tile = tile_option.unwrap();
+        current_direction = get_next_direction(current_direction, tile);
+
+        if current_direction == Direction::None
+        {
+            return Err(format!("the loop is broken at '{}' ({}, {})", tile, current_position.x, current_position.y));
+        }
+
         current_position += convert_to_coordinates(current_direction);
     }
 
-    return vertices;
+    return Ok(vertices);
 }
 
-fn determinant(pos1: &Position, pos2: &Position) -> i64
+// Scanning each row, crossing a loop tile that connects upwards flips between outside and inside
+fn classify(world: &World, vertices: &Vertices) -> Tiles
 {
-    return pos1.x * pos2.y - pos1.y * pos2.x;
-}
-
-fn calculate_area(positions: &Vertices) -> u64
-{
-    let mut area = 0;
+    let mut tiles: Tiles = world.iter().map(|row| vec![Tile::Outside; row.len()]).collect();
 
-    for i in 1..positions.len()
+    for vertex in vertices
     {
-        area += determinant(&positions[i-1], &positions[i]);
+        tiles[vertex.y as usize][vertex.x as usize] = Tile::Loop;
     }
 
-    area += determinant(&positions[positions.len()-1], &positions[0]);
+    for (i, row) in tiles.iter_mut().enumerate()
+    {
+        let mut inside = false;
 
-    return abs(area / 2) as u64;
+        for (j, tile) in row.iter_mut().enumerate()
+        {
+            if *tile == Tile::Loop
+            {
+                inside ^= matches!(world[i][j], '|' | 'L' | 'J');
+            }
+            else if inside
+            {
+                *tile = Tile::Inside;
+            }
+        }
+    }
+
+    return tiles;
 }
 
-fn calculate_pick(area: u64, number_of_vertices: u64) -> u64
+fn render(world: &World, vertices: &Vertices) -> String
 {
-    return area - (number_of_vertices / 2) + 1;
+    let tiles = classify(world, vertices);
+    let mut lines: Vec<String> = Vec::new();
+
+    for (i, row) in tiles.iter().enumerate()
+    {
+        lines.push(row.iter().enumerate().map(|(j, tile)| match tile
+        {
+            Tile::Loop => to_box_drawing(world[i][j]),
+            Tile::Inside => 'I',
+            Tile::Outside => 'O'
+        }).collect());
+    }
+
+    let inside = tiles.iter().flatten().filter(|&&tile| tile == Tile::Inside).count();
+    lines.push(format!("{} tiles inside", inside));
+
+    return lines.join("\n");
 }
 
 fn part1(vertices: &Vertices) -> u64
@@ -201,10 +330,14 @@ fn part1(vertices: &Vertices) -> u64
 
 fn part2(vertices: &Vertices) -> u64
 {
-    let area = calculate_area(&vertices);
-    let number_of_vertices = vertices.len() as u64;
+    let polygon: Polygon = Polygon::new(vertices.iter().map(|vertex| (vertex.x, vertex.y)).collect());
+    return polygon.interior_points() as u64;
+}
 
-    return calculate_pick(area, number_of_vertices)
+fn fail(message: String) -> !
+{
+    eprintln!("{}", message);
+    std::process::exit(1);
 }
 
 fn main()
@@ -212,16 +345,22 @@ fn main()
     let contents: String = fs::read_to_string(FILE_PATH)
         .expect("Should have been able to read the file");
 
-    let world: World = parse_world(&contents);
-    let start = find_start(&world);
-    let max_position =
-        Position
-        {
-            x: contents.lines().last().unwrap().len() as i64 - 1,
-            y: contents.lines().count() as i64 - 1
-        };
+    let mut world: World = parse_world(&contents);
+    let start = find_start(&world)
+        .unwrap_or_else(|reason| fail(format!("Can't find the start: {}", reason)));
 
-    let vertices = calculate_vertices(&world, &start, &max_position);
+    resolve_start(&mut world, &start)
+        .unwrap_or_else(|reason| fail(format!("Can't infer the start pipe: {}", reason)));
+
+    let vertices = calculate_vertices(&world, &start)
+        .unwrap_or_else(|reason| fail(format!("Can't follow the loop: {}", reason)));
+
+    // Usage: render
+    if env::args().nth(1).is_some_and(|mode| mode == "render")
+    {
+        println!("{}", render(&world, &vertices));
+        return;
+    }
 
     let part1_result: u64 = part1(&vertices);
     let part2_result: u64 = part2(&vertices);
@@ -1,14 +1,25 @@
-use std::{fs, cmp::{max, min}};
+use std::{fs, env, cmp::{max, min}, collections::BinaryHeap};
 
 const FILE_PATH : &str = "input.txt";
 
-const PART1_FACTOR: u64 = 2 - 1;
-const PART2_FACTOR: u64 = 1000000 - 1;
+const PART1_EXPANSION: Expansion = Expansion{rows: 2, columns: 2};
+const PART2_EXPANSION: Expansion = Expansion{rows: 1000000, columns: 1000000};
 
 type Expanded = Vec<u64>;
 type Matrix = Vec<Vec<char>>;
 type Galaxies = Vec<Position>;
 
+// How many lines each empty row or column turns into
+#[derive(Clone, Copy)]
+struct Expansion
+{
+    rows: u64,
+    columns: u64
+}
+
+// Galaxy pair as indices into the galaxies, lowest first
+type Pair = (u64, usize, usize);
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 struct Position
 {
@@ -92,40 +103,23 @@ fn find_expanded(matrix: &Matrix) -> (Expanded, Expanded)
     return (find_expanded_rows(&matrix), find_expanded_columns(&matrix));
 }
 
-fn update_galaxies_x(galaxies: &mut Galaxies, expanded_rows: &Expanded, factor: u64)
+// Every empty line before a coordinate adds factor - 1 to it. Empty lines are found in increasing order, so they can be counted by bisection
+fn expand(coordinate: u64, expanded: &Expanded, factor: u64) -> u64
 {
-    for galaxy in galaxies
-    {
-        for expanded_row in expanded_rows.iter().rev()
-        {
-            if galaxy.x > *expanded_row
-            {
-                galaxy.x += factor;
-            }
-        }
-    }
+    return coordinate + expanded.partition_point(|line| *line < coordinate) as u64 * (factor - 1);
 }
 
-fn update_galaxies_y(galaxies: &mut Galaxies, expanded_columns: &Expanded, factor: u64)
+fn update_galaxies(galaxies: &mut Galaxies, expanded_rows: &Expanded, expanded_columns: &Expanded, expansion: Expansion)
 {
+    assert!(expansion.rows > 0 && expansion.columns > 0, "Expansion factors should be at least 1");
+
     for galaxy in galaxies
     {
-        for expanded_column in expanded_columns.iter().rev()
-        {
-            if galaxy.y > *expanded_column
-            {
-                galaxy.y += factor;
-            }
-        }
+        galaxy.x = expand(galaxy.x, expanded_rows, expansion.rows);
+        galaxy.y = expand(galaxy.y, expanded_columns, expansion.columns);
     }
 }
 
-fn update_galaxies(galaxies: &mut Galaxies, expanded_rows: &Expanded, expanded_columns: &Expanded, factor: u64)
-{
-    update_galaxies_x(galaxies, expanded_rows, factor);
-    update_galaxies_y(galaxies, expanded_columns, factor);
-}
-
 fn distance(pos1: &Position, pos2: &Position) -> u64
 {
     let x_distance = max(pos1.x, pos2.x) - min(pos1.x, pos2.x);
@@ -133,33 +127,119 @@ fn distance(pos1: &Position, pos2: &Position) -> u64
     return x_distance + y_distance;
 }
 
-fn calculate_distances(galaxies: &Galaxies) -> u64
+// Once sorted, the value at i lies at or after the i values before it, so it adds i * value minus their sum
+fn axis_distances(mut values: Vec<u64>) -> u64
 {
+    values.sort();
+
     let mut result = 0;
+    let mut prefix = 0;
+
+    for (i, value) in values.iter().enumerate()
+    {
+        result += i as u64 * value - prefix;
+        prefix += value;
+    }
+
+    return result;
+}
+
+// Manhattan distances split per axis, so the sum over all pairs needs no pairing at all
+fn calculate_distances(galaxies: &Galaxies) -> u64
+{
+    return axis_distances(galaxies.iter().map(|galaxy| galaxy.x).collect())
+        + axis_distances(galaxies.iter().map(|galaxy| galaxy.y).collect());
+}
+
+// The k pairs that come first under the given key, keeping only the best k seen in a heap while going through every pair
+fn select_pairs<TKey: Ord, TKeyFn: Fn(u64) -> TKey>(galaxies: &Galaxies, k: usize, key: TKeyFn) -> Vec<Pair>
+{
+    let mut heap: BinaryHeap<(TKey, usize, usize)> = BinaryHeap::new();
 
     for i in 0..galaxies.len()
     {
         for j in i+1..galaxies.len()
         {
-            result += distance(&galaxies[i], &galaxies[j]);
+            heap.push((key(distance(&galaxies[i], &galaxies[j])), i, j));
+
+            if heap.len() > k
+            {
+                heap.pop();
+            }
         }
     }
 
-    return result;
+    return heap.into_sorted_vec().into_iter().map(|(_, i, j)| (distance(&galaxies[i], &galaxies[j]), i, j)).collect();
+}
+
+fn nearest_pairs(galaxies: &Galaxies, k: usize) -> Vec<Pair>
+{
+    return select_pairs(galaxies, k, |distance| distance);
+}
+
+fn farthest_pairs(galaxies: &Galaxies, k: usize) -> Vec<Pair>
+{
+    return select_pairs(galaxies, k, |distance| std::cmp::Reverse(distance));
 }
 
 fn part1(galaxies: &mut Galaxies, expanded_rows: &Expanded, expanded_columns: &Expanded) -> u64
 {
-    update_galaxies(galaxies, &expanded_rows, &expanded_columns, PART1_FACTOR);
+    update_galaxies(galaxies, &expanded_rows, &expanded_columns, PART1_EXPANSION);
     return calculate_distances(&galaxies);
 }
 
 fn part2(galaxies: &mut Galaxies, expanded_rows: &Expanded, expanded_columns: &Expanded) -> u64
 {
-    update_galaxies(galaxies, &expanded_rows, &expanded_columns, PART2_FACTOR);
+    update_galaxies(galaxies, &expanded_rows, &expanded_columns, PART2_EXPANSION);
     return calculate_distances(&galaxies);
 }
 
+const USAGE: &str = "Usage: sum <rows> <columns> | nearest <k> <rows> <columns> | farthest <k> <rows> <columns>";
+
+fn number(args: &Vec<String>, n: usize) -> Result<u64, String>
+{
+    let arg = args.get(n).ok_or(format!("Missing argument {}", n))?;
+    return arg.parse::<u64>().map_err(|_| format!("Argument \"{}\" should be a number", arg));
+}
+
+fn explore(galaxies: &mut Galaxies, expanded_rows: &Expanded, expanded_columns: &Expanded, args: &Vec<String>) -> Result<(), String>
+{
+    let (k, first_factor) = match args[0].as_str()
+    {
+        "sum" => (0, 1),
+        "nearest" | "farthest" => (number(args, 1)? as usize, 2),
+        _ => return Err(String::from("Mode should be one of sum, nearest or farthest"))
+    };
+
+    let expansion: Expansion = Expansion{rows: number(args, first_factor)?, columns: number(args, first_factor + 1)?};
+
+    if expansion.rows == 0 || expansion.columns == 0
+    {
+        return Err(String::from("Expansion factors should be at least 1"));
+    }
+
+    update_galaxies(galaxies, expanded_rows, expanded_columns, expansion);
+
+    let pairs = match args[0].as_str()
+    {
+        "nearest" => nearest_pairs(galaxies, k),
+        "farthest" => farthest_pairs(galaxies, k),
+        _ =>
+        {
+            println!("{}", calculate_distances(galaxies));
+            return Ok(());
+        }
+    };
+
+    // Galaxies are numbered from 1 in reading order, as in the puzzle
+    for (distance, i, j) in pairs
+    {
+        println!("{} - {}: {}", i + 1, j + 1, distance);
+    }
+
+    return Ok(());
+}
+
 fn main()
 {
     let contents: String = fs::read_to_string(FILE_PATH)
@@ -169,6 +249,19 @@ fn main()
     let (expanded_rows, expanded_columns) = find_expanded(&matrix);
     let galaxies: Galaxies = parse_galaxies(&matrix);
 
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if !args.is_empty()
+    {
+        if let Err(message) = explore(&mut galaxies.clone(), &expanded_rows, &expanded_columns, &args)
+        {
+            eprintln!("{}\n{}", message, USAGE);
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
     let part1_result: u64 = part1(&mut galaxies.clone(), &expanded_rows, &expanded_columns);
     let part2_result: u64 = part2(&mut galaxies.clone(), &expanded_rows, &expanded_columns);
     
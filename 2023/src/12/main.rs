@@ -1,8 +1,14 @@
 // Used idea from https://github.com/hyper-neutrino/advent-of-code/blob/main/2023/day12p1.py
 
-use std::{fs, cmp::min, collections::HashMap};
+mod nonogram;
+
+use std::{fs, env, cmp::min, thread, io::{BufRead, BufReader}, sync::{Arc, Mutex, mpsc, atomic::{AtomicBool, Ordering}}};
 
 const FILE_PATH : &str = "input.txt";
+const BATCH_SIZE: usize = 4096;
+const OVERFLOW: &str = "Arrangement count overflows a u64";
+const PART1_UNFOLDING: Unfolding = Unfolding{factor: 1, separator: None};
+const PART2_UNFOLDING: Unfolding = Unfolding{factor: 5, separator: Some(Spring::Unknown)};
 
 #[derive(PartialEq, Eq, Clone, Copy, Hash)]
 enum Spring
@@ -22,11 +28,22 @@ impl Spring
             _ => Err(()),
         }
     }
+
+    pub fn to_char(&self) -> char
+    {
+        return match self
+        {
+            Spring::Operational => '.',
+            Spring::Damaged => '#',
+            Spring::Unknown => '?'
+        }
+    }
 }
 
 type SpringConfiguration = Vec<Spring>;
 type Grouping = Vec<u64>;
-type Cache = HashMap<Configuration, u64>;
+// None marks a count that overflowed
+type Table = Vec<Option<u64>>;
 
 #[derive(PartialEq, Eq, Clone, Hash)]
 struct Configuration
@@ -37,128 +54,362 @@ struct Configuration
 
 type Configurations = Vec<Configuration>;
 
-fn parse_configuration(configuration_str: &str) -> SpringConfiguration
+struct Unfolding
+{
+    factor: usize,
+    separator: Option<Spring>
+}
+
+fn parse_configuration(configuration_str: &str) -> Result<SpringConfiguration, String>
 {
-    return configuration_str.chars().map(|c| Spring::from(c).unwrap()).collect();
+    return configuration_str.chars()
+        .map(|c| Spring::from(c).map_err(|_| format!("Unknown spring '{}' in \"{}\"", c, configuration_str)))
+        .collect();
 }
 
-fn parse(contents: &String) -> Configurations
+fn parse_line(line: &str) -> Result<Configuration, String>
+{
+    let (springs, groups) = line.split_once(" ").ok_or(format!("Line \"{}\" should be springs and groups separated by a space", line))?;
+    let configuration: SpringConfiguration = parse_configuration(springs)?;
+    let grouping: Grouping = groups.split(",")
+        .map(|group| group.parse::<u64>().map_err(|_| format!("Group \"{}\" in \"{}\" should be a number", group, line)))
+        .collect::<Result<Grouping, String>>()?;
+
+    return Ok(Configuration{ configuration: configuration, grouping: grouping });
+}
+
+fn parse(contents: &String) -> Result<Configurations, String>
 {
     let mut configurations: Configurations = Configurations::new();
 
     for line in contents.lines()
     {
-        let line_split: Vec<&str> = line.split(" ").collect();
-        let configuration: SpringConfiguration = parse_configuration(line_split[0]);
-        let grouping: Grouping = line_split[1].split(",").map(|c| c.parse::<u64>().unwrap()).collect();
-
-        configurations.push(Configuration{ configuration: configuration, grouping: grouping });
+        configurations.push(parse_line(line)?);
     }
 
-    return configurations;
+    return Ok(configurations);
 }
 
-fn count(configuration: Configuration, cache: &mut Cache) -> u64
+// Whole groups are placed at once, so the current run never has to be part of the state
+struct Counter<'a>
 {
-    if configuration.configuration.is_empty()
+    configuration: &'a Configuration,
+    operational_before: Vec<usize>,
+    table: Table
+}
+
+impl<'a> Counter<'a>
+{
+    fn new(configuration: &'a Configuration) -> Counter<'a>
     {
-        return configuration.grouping.is_empty() as u64;
+        let springs = configuration.configuration.len();
+        let groups = configuration.grouping.len();
+
+        let mut operational_before: Vec<usize> = vec![0; springs + 1];
+
+        for (i, spring) in configuration.configuration.iter().enumerate()
+        {
+            operational_before[i + 1] = operational_before[i] + (*spring == Spring::Operational) as usize;
+        }
+
+        let mut counter: Counter = Counter{configuration: configuration, operational_before: operational_before, table: vec![Some(0); (springs + 1) * (groups + 1)]};
+        counter.table[springs * (groups + 1) + groups] = Some(1);
+
+        for position in (0..springs).rev()
+        {
+            for group in 0..=groups
+            {
+                counter.table[position * (groups + 1) + group] = counter.skip(position, group).zip(counter.place(position, group)).and_then(|(skipped, placed)| skipped.checked_add(placed));
+            }
+        }
+
+        return counter;
     }
 
-    if configuration.grouping.is_empty()
+    fn get(&self, position: usize, group: usize) -> Option<u64>
     {
-        return configuration.configuration.iter().all(|elem| *elem != Spring::Damaged) as u64;
+        return self.table[position * (self.configuration.grouping.len() + 1) + group];
     }
 
-    if cache.contains_key(&configuration)
+    fn fits(&self, position: usize, group: usize) -> bool
     {
-        return cache[&configuration];
+        let springs: &SpringConfiguration = &self.configuration.configuration;
+
+        if group == self.configuration.grouping.len() || springs[position] == Spring::Operational
+        {
+            return false;
+        }
+
+        let end = position + self.configuration.grouping[group] as usize;
+
+        return (end <= springs.len())
+            && (self.operational_before[end] == self.operational_before[position])
+            && ((end == springs.len()) || (springs[end] != Spring::Damaged));
     }
 
-    let mut result: u64 = 0;
+    // Arrangements where the spring at the position is operational
+    fn skip(&self, position: usize, group: usize) -> Option<u64>
+    {
+        if self.configuration.configuration[position] == Spring::Damaged
+        {
+            return Some(0);
+        }
+
+        return self.get(position + 1, group);
+    }
 
-    if configuration.configuration[0] == Spring::Operational || configuration.configuration[0] == Spring::Unknown
+    // Arrangements where the group starts at the position, followed by an operational spring
+    fn place(&self, position: usize, group: usize) -> Option<u64>
     {
-        result += count(
-            Configuration
-            {
-                configuration: configuration.configuration[1..].to_vec(),
-                grouping: configuration.grouping.clone()
-            }, cache);
+        if !self.fits(position, group)
+        {
+            return Some(0);
+        }
+
+        let next = min(position + self.configuration.grouping[group] as usize + 1, self.configuration.configuration.len());
+        return self.get(next, group + 1);
     }
 
-    if configuration.configuration[0] == Spring::Damaged || configuration.configuration[0] == Spring::Unknown
+    fn count(&self) -> Option<u64>
     {
-        if (configuration.grouping[0] <= configuration.configuration.len() as u64)
-            && (configuration.configuration[0..configuration.grouping[0] as usize].iter().all(|elem| *elem != Spring::Operational))
-            && ((configuration.grouping[0] == configuration.configuration.len() as u64) || (configuration.configuration[configuration.grouping[0] as usize] != Spring::Damaged))
+        return self.get(0, 0);
+    }
+
+    fn enumerate(&self) -> Vec<SpringConfiguration>
+    {
+        let mut arrangements: Vec<SpringConfiguration> = Vec::new();
+        let mut current: SpringConfiguration = SpringConfiguration::new();
+
+        self.enumerate_from(0, 0, &mut current, &mut arrangements);
+        return arrangements;
+    }
+
+    fn enumerate_from(&self, position: usize, group: usize, current: &mut SpringConfiguration, arrangements: &mut Vec<SpringConfiguration>)
+    {
+        let springs = self.configuration.configuration.len();
+
+        if position == springs
         {
-            result += count(
-                Configuration
-                {
-                    configuration: configuration.configuration[(min(configuration.grouping[0] as usize + 1, configuration.configuration.len()))..].to_vec(),
-                    grouping: configuration.grouping[1..].to_vec()
-                }, cache);
+            arrangements.push(current.clone());
+            return;
+        }
+
+        if self.skip(position, group) != Some(0)
+        {
+            current.push(Spring::Operational);
+            self.enumerate_from(position + 1, group, current, arrangements);
+            current.pop();
+        }
+
+        if self.place(position, group) != Some(0)
+        {
+            let length = self.configuration.grouping[group] as usize;
+            let next = min(position + length + 1, springs);
+
+            current.extend(vec![Spring::Damaged; length]);
+            current.extend(vec![Spring::Operational; next - position - length]);
+            self.enumerate_from(next, group + 1, current, arrangements);
+            current.truncate(position);
         }
     }
+}
 
-    cache.insert(configuration, result);
-    return result;
+fn count(configuration: &Configuration) -> Result<u64, String>
+{
+    return Counter::new(configuration).count().ok_or(String::from(OVERFLOW));
 }
 
-fn fix_input(configuration: &mut SpringConfiguration) -> SpringConfiguration
+fn unfold(configuration: &Configuration, unfolding: &Unfolding) -> Configuration
 {
-    configuration.push(Spring::Unknown);
+    let mut springs: SpringConfiguration = SpringConfiguration::new();
+
+    for i in 0..unfolding.factor
+    {
+        if i > 0 && unfolding.separator.is_some()
+        {
+            springs.push(unfolding.separator.unwrap());
+        }
 
-    let mut repeated_config = configuration.repeat(5);
-    repeated_config.pop();
+        springs.extend(configuration.configuration.iter());
+    }
 
-    return repeated_config;
+    return Configuration{ configuration: springs, grouping: configuration.grouping.repeat(unfolding.factor) };
 }
 
-fn part1(configurations: &Configurations) -> u64
+// Sending stops once a worker has failed, or once every worker is gone and the channel is closed
+fn feed<TRead: BufRead>(reader: TRead, sender: mpsc::SyncSender<Vec<String>>, failed: &AtomicBool) -> Result<(), String>
 {
-    let mut cache: Cache = Cache::new();
-    let mut result = 0;
+    let mut batch: Vec<String> = Vec::with_capacity(BATCH_SIZE);
 
-    for configuration in configurations
+    for line in reader.lines()
     {
-        result += count(configuration.clone(), &mut cache);
+        let line = line.map_err(|error| format!("Should have been able to read the line: {}", error))?;
+
+        if line.is_empty()
+        {
+            continue;
+        }
+
+        batch.push(line);
+
+        if batch.len() == BATCH_SIZE
+        {
+            if failed.load(Ordering::Relaxed) || sender.send(batch).is_err()
+            {
+                return Ok(());
+            }
+
+            batch = Vec::with_capacity(BATCH_SIZE);
+        }
     }
 
-    return result;
+    sender.send(batch).ok();
+    return Ok(());
+}
+
+// Lines are handed out in batches through a bounded channel, so only a few batches are ever in memory.
+// Each worker holds its own handle on the receiver, so the channel closes as soon as they have all stopped
+fn count_stream<TRead: BufRead>(reader: TRead, unfolding: &Unfolding) -> Result<u64, String>
+{
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let (sender, receiver) = mpsc::sync_channel::<Vec<String>>(2 * threads);
+    let receiver = Arc::new(Mutex::new(receiver));
+    let failed = AtomicBool::new(false);
+
+    return thread::scope(|scope|
+    {
+        let workers: Vec<_> = (0..threads).map(|_|
+        {
+            let (receiver, failed) = (Arc::clone(&receiver), &failed);
+
+            scope.spawn(move || -> Result<u64, String>
+            {
+                let mut result: u64 = 0;
+
+                while !failed.load(Ordering::Relaxed)
+                {
+                    let batch = receiver.lock().unwrap().recv();
+
+                    if batch.is_err()
+                    {
+                        break;
+                    }
+
+                    for line in batch.unwrap()
+                    {
+                        let total = parse_line(&line)
+                            .and_then(|configuration| count(&unfold(&configuration, unfolding)))
+                            .and_then(|arrangements| result.checked_add(arrangements).ok_or(String::from(OVERFLOW)));
+
+                        match total
+                        {
+                            Ok(total) => result = total,
+                            Err(message) =>
+                            {
+                                failed.store(true, Ordering::Relaxed);
+                                return Err(message);
+                            }
+                        }
+                    }
+                }
+
+                return Ok(result);
+            })
+        }).collect();
+
+        drop(receiver);
+
+        let fed = feed(reader, sender, &failed);
+        let results: Vec<Result<u64, String>> = workers.into_iter().map(|worker| worker.join().unwrap()).collect();
+
+        fed?;
+        return results.into_iter().try_fold(0u64, |total, result| total.checked_add(result?).ok_or(String::from(OVERFLOW)));
+    });
+}
+
+fn open(path: &str) -> BufReader<fs::File>
+{
+    return BufReader::new(fs::File::open(path).expect("Should have been able to read the file"));
+}
+
+fn part1(path: &str) -> Result<u64, String>
+{
+    return count_stream(open(path), &PART1_UNFOLDING);
 }
 
-fn part2(configurations: &Configurations) -> u64
+fn part2(path: &str) -> Result<u64, String>
 {
-    let mut cache: Cache = Cache::new();
-    let mut result = 0;
+    return count_stream(open(path), &PART2_UNFOLDING);
+}
+
+fn fail(message: String) -> !
+{
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
 
-    for configuration in configurations
+// Usage: arrangements | unfold <factor> [separator] | nonogram <path>, e.g. "unfold 7 ."
+fn explore(args: &Vec<String>) -> Result<(), String>
+{
+    match args[0].as_str()
     {
-        let new_configuration =
-            Configuration
+        "arrangements" =>
+        {
+            let contents: String = fs::read_to_string(FILE_PATH)
+                .expect("Should have been able to read the file");
+
+            for configuration in &parse(&contents)?
             {
-                configuration: fix_input(&mut configuration.configuration.clone()),
-                grouping: configuration.grouping.repeat(5)
+                for arrangement in Counter::new(configuration).enumerate()
+                {
+                    println!("{}", arrangement.iter().map(|spring| spring.to_char()).collect::<String>());
+                }
+                println!();
+            }
+        }
+        "unfold" =>
+        {
+            let factor = args.get(1).ok_or(String::from("Unfold needs a factor"))?
+                .parse::<usize>().map_err(|_| String::from("Factor should be a number"))?;
+
+            let separator = match args.get(2).map(|separator| separator.chars().collect::<Vec<char>>())
+            {
+                None => None,
+                Some(chars) if chars.len() == 1 => Some(Spring::from(chars[0]).map_err(|_| format!("Separator '{}' should be one of . # ?", chars[0]))?),
+                Some(_) => return Err(String::from("Separator should be a single spring, one of . # ?"))
             };
 
-        result += count(new_configuration, &mut cache);
+            println!("{}", count_stream(open(FILE_PATH), &Unfolding{factor: factor, separator: separator})?);
+        }
+        "nonogram" =>
+        {
+            let path = args.get(1).ok_or(String::from("Nonogram needs a path"))?;
+
+            match nonogram::solve(&nonogram::parse(path)?)
+            {
+                Some(grid) => println!("{}", nonogram::render(&grid)),
+                None => println!("no solution")
+            }
+        }
+        _ => return Err(String::from("Mode should be one of arrangements, unfold or nonogram"))
     }
 
-    return result;
+    return Ok(());
 }
 
 fn main()
 {
-    let contents: String = fs::read_to_string(FILE_PATH)
-        .expect("Should have been able to read the file");
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    let configurations: Configurations = parse(&contents);
+    if !args.is_empty()
+    {
+        explore(&args).unwrap_or_else(|message| fail(message));
+        return;
+    }
 
-    let part1_result: u64 = part1(&configurations);
-    let part2_result: u64 = part2(&configurations);
+    let part1_result: u64 = part1(FILE_PATH).unwrap_or_else(|message| fail(message));
+    let part2_result: u64 = part2(FILE_PATH).unwrap_or_else(|message| fail(message));
     
     println!("{}", part1_result);
     println!("{}", part2_result);
-}
\ No newline at end of file
+}
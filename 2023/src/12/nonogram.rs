@@ -0,0 +1,200 @@
+use std::fs;
+
+use super::{Spring, SpringConfiguration, Grouping, Configuration, Counter};
+
+type Grid = Vec<SpringConfiguration>;
+type Clues = Vec<Grouping>;
+
+pub struct Nonogram
+{
+    rows: Clues,
+    columns: Clues
+}
+
+fn parse_clues(block: &str) -> Result<Clues, String>
+{
+    return block.lines()
+        .map(|line| line.split(",")
+            .map(|c| c.trim().parse::<u64>().map_err(|_| format!("Clue \"{}\" in \"{}\" should be a number", c.trim(), line)))
+            .filter(|group| *group != Ok(0))
+            .collect::<Result<Grouping, String>>())
+        .collect();
+}
+
+// Cells a clue takes up at the least, with a gap between consecutive groups
+fn span(clue: &Grouping) -> usize
+{
+    return clue.iter().sum::<u64>() as usize + clue.len().saturating_sub(1);
+}
+
+fn check_clues(name: &str, clues: &Clues, length: usize) -> Result<(), String>
+{
+    for (i, clue) in clues.iter().enumerate()
+    {
+        if span(clue) > length
+        {
+            return Err(format!("The clue of {} {} needs {} cells but the grid only has {}", name, i + 1, span(clue), length));
+        }
+    }
+
+    return Ok(());
+}
+
+// Row clues, a blank line and then column clues, one comma separated line each. A 0 stands for an empty line
+pub fn parse(path: &str) -> Result<Nonogram, String>
+{
+    let contents: String = fs::read_to_string(path)
+        .map_err(|error| format!("Should have been able to read {}: {}", path, error))?;
+
+    let (rows_str, columns_str) = contents.trim().split_once("\n\n").ok_or(String::from("Row and column clues should be separated by a blank line"))?;
+    let nonogram: Nonogram = Nonogram{rows: parse_clues(rows_str)?, columns: parse_clues(columns_str)?};
+
+    check_clues("row", &nonogram.rows, nonogram.columns.len())?;
+    check_clues("column", &nonogram.columns, nonogram.rows.len())?;
+
+    let row_cells: u64 = nonogram.rows.iter().flatten().sum();
+    let column_cells: u64 = nonogram.columns.iter().flatten().sum();
+
+    if row_cells != column_cells
+    {
+        return Err(format!("The row clues fill {} cells but the column clues fill {}", row_cells, column_cells));
+    }
+
+    return Ok(nonogram);
+}
+
+// Only whether a count is zero matters here, and an overflowed count is never zero
+fn count_with(line: &SpringConfiguration, clue: &Grouping, index: usize, spring: Spring) -> Option<u64>
+{
+    let mut configuration: Configuration = Configuration{configuration: line.clone(), grouping: clue.clone()};
+    configuration.configuration[index] = spring;
+
+    return Counter::new(&configuration).count();
+}
+
+// Fixes every cell that takes the same value in all arrangements of the line. Returns None on a contradiction
+fn deduce(line: &SpringConfiguration, clue: &Grouping) -> Option<SpringConfiguration>
+{
+    let mut deduced: SpringConfiguration = line.clone();
+
+    if Counter::new(&Configuration{configuration: line.clone(), grouping: clue.clone()}).count() == Some(0)
+    {
+        return None;
+    }
+
+    for i in 0..line.len()
+    {
+        if line[i] != Spring::Unknown
+        {
+            continue;
+        }
+
+        let damaged = count_with(line, clue, i, Spring::Damaged);
+        let operational = count_with(line, clue, i, Spring::Operational);
+
+        if damaged == Some(0)
+        {
+            deduced[i] = Spring::Operational;
+        }
+        else if operational == Some(0)
+        {
+            deduced[i] = Spring::Damaged;
+        }
+    }
+
+    return Some(deduced);
+}
+
+fn column(grid: &Grid, j: usize) -> SpringConfiguration
+{
+    return grid.iter().map(|row| row[j]).collect();
+}
+
+fn propagate(grid: &mut Grid, nonogram: &Nonogram) -> bool
+{
+    let mut changed = true;
+
+    while changed
+    {
+        changed = false;
+
+        for i in 0..grid.len()
+        {
+            let deduced_option = deduce(&grid[i], &nonogram.rows[i]);
+
+            if deduced_option.is_none()
+            {
+                return false;
+            }
+
+            let deduced: SpringConfiguration = deduced_option.unwrap();
+            changed |= deduced != grid[i];
+            grid[i] = deduced;
+        }
+
+        for j in 0..nonogram.columns.len()
+        {
+            let line: SpringConfiguration = column(grid, j);
+            let deduced_option = deduce(&line, &nonogram.columns[j]);
+
+            if deduced_option.is_none()
+            {
+                return false;
+            }
+
+            let deduced: SpringConfiguration = deduced_option.unwrap();
+            changed |= deduced != line;
+
+            for i in 0..grid.len()
+            {
+                grid[i][j] = deduced[i];
+            }
+        }
+    }
+
+    return true;
+}
+
+fn backtrack(mut grid: Grid, nonogram: &Nonogram) -> Option<Grid>
+{
+    if !propagate(&mut grid, nonogram)
+    {
+        return None;
+    }
+
+    let unknown_option = grid.iter().enumerate()
+        .find_map(|(i, row)| row.iter().position(|spring| *spring == Spring::Unknown).map(|j| (i, j)));
+
+    if unknown_option.is_none()
+    {
+        return Some(grid);
+    }
+
+    let (i, j) = unknown_option.unwrap();
+
+    for guess in [Spring::Damaged, Spring::Operational]
+    {
+        let mut next_grid: Grid = grid.clone();
+        next_grid[i][j] = guess;
+
+        let solution = backtrack(next_grid, nonogram);
+
+        if solution.is_some()
+        {
+            return solution;
+        }
+    }
+
+    return None;
+}
+
+pub fn solve(nonogram: &Nonogram) -> Option<Grid>
+{
+    let grid: Grid = vec![vec![Spring::Unknown; nonogram.columns.len()]; nonogram.rows.len()];
+    return backtrack(grid, nonogram);
+}
+
+pub fn render(grid: &Grid) -> String
+{
+    return grid.iter().map(|row| row.iter().map(|spring| spring.to_char()).collect::<String>()).collect::<Vec<String>>().join("\n");
+}
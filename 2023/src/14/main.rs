@@ -1,13 +1,16 @@
-use std::{fs, collections::HashMap};
+use std::{fs, env, collections::HashMap};
 
 const FILE_PATH : &str = "input.txt";
 const NUMBER_CYCLES: u64 = 1000000000;
+const SPIN_CYCLE: &str = "NWSE";
+const MAX_WIDTH: usize = Row::BITS as usize;
 
-type Row = Vec<Obstacle>;
-type Matrix = Vec<Row>;
-type Configuration = Vec<Position>;
+// Each row is packed into a single word, with bit j standing for column j
+type Row = u128;
+type Rows = Vec<Row>;
+type Configuration = Rows;
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
 enum Direction
 {
     Up,
@@ -24,11 +27,33 @@ enum Obstacle
     None
 }
 
-#[derive(PartialEq, Eq, Hash)]
-struct Position
+// Run of consecutive columns in a row not interrupted by a cube
+struct Segment
 {
-    x: usize,
-    y: usize
+    start: usize,
+    length: usize,
+    mask: Row
+}
+
+struct Platform
+{
+    width: usize,
+    rounds: Rows,
+    cubes: Rows,
+    segments: Vec<Vec<Segment>>
+}
+
+impl Direction
+{
+    pub fn from(c: char) -> Result<Direction, ()> {
+        return match c {
+            'N' => Ok(Direction::Up),
+            'S' => Ok(Direction::Down),
+            'W' => Ok(Direction::Left),
+            'E' => Ok(Direction::Right),
+            _ => Err(()),
+        }
+    }
 }
 
 impl Obstacle
@@ -47,144 +72,179 @@ fn convert_to_shift(direction: Direction) -> i64
 {
     return match direction
     {
-        Direction::Left => 1,
-        Direction::Right => -1,
-        Direction::Up => 1,
-        Direction::Down => -1
+        Direction::Left => -1,
+        Direction::Right => 1,
+        Direction::Up => -1,
+        Direction::Down => 1
     }
 }
 
-fn parse(contents: &String) -> Matrix
+fn low_bits(count: usize) -> Row
 {
-    let mut matrix: Matrix = Matrix::new();
-
-    for line in contents.lines()
+    if count >= MAX_WIDTH
     {
-        matrix.push(line.chars().map(|c| Obstacle::from(c).unwrap()).collect());
+        return Row::MAX;
     }
 
-    return matrix;
+    return (1 << count) - 1;
 }
 
-fn calculate_vertical_change(matrix: &mut Matrix, i: usize, j: usize, last_obstacles: &mut Vec<i64>, direction: Direction)
+fn calculate_segments(cubes: Row, width: usize) -> Vec<Segment>
 {
-    assert_ne!(direction, Direction::Left);
-    assert_ne!(direction, Direction::Right);
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut start: usize = 0;
 
-    let shift = convert_to_shift(direction);
-
-    if matrix[i][j] == Obstacle::Cube
+    for j in 0..=width
     {
-        last_obstacles[j] = i as i64;
-    }
-
-    if matrix[i][j] == Obstacle::Round
-    {
-        if i != (last_obstacles[j] + shift) as usize
+        if j == width || (cubes >> j) & 1 == 1
         {
-            matrix[(last_obstacles[j] + shift) as usize][j] = Obstacle::Round;
-            matrix[i][j] = Obstacle::None;
-        }
+            let length = j - start;
+
+            if length > 0
+            {
+                segments.push(Segment{start: start, length: length, mask: low_bits(length) << start});
+            }
 
-        last_obstacles[j] += shift as i64;
+            start = j + 1;
+        }
     }
+
+    return segments;
 }
 
-fn calculate_horizontal_change(matrix: &mut Matrix, i: usize, j: usize, last_obstacles: &mut Vec<i64>, direction: Direction)
+fn parse(contents: &String) -> Platform
 {
-    assert_ne!(direction, Direction::Up);
-    assert_ne!(direction, Direction::Down);
-
-    let shift = convert_to_shift(direction);
+    let mut platform: Platform = Platform{width: 0, rounds: Rows::new(), cubes: Rows::new(), segments: Vec::new()};
 
-    if matrix[i][j] == Obstacle::Cube
+    for line in contents.lines()
     {
-        last_obstacles[i] = j as i64;
-    }
+        platform.width = line.len();
+        assert!(platform.width <= MAX_WIDTH, "Rows wider than {} columns are not supported", MAX_WIDTH);
 
-    if matrix[i][j] == Obstacle::Round
-    {
-        if j != (last_obstacles[i] + shift) as usize
+        let mut rounds: Row = 0;
+        let mut cubes: Row = 0;
+
+        for (j, c) in line.chars().enumerate()
         {
-            matrix[i][(last_obstacles[i] + shift) as usize] = Obstacle::Round;
-            matrix[i][j] = Obstacle::None;
+            match Obstacle::from(c).unwrap()
+            {
+                Obstacle::Round => rounds |= 1 << j,
+                Obstacle::Cube => cubes |= 1 << j,
+                Obstacle::None => ()
+            }
         }
 
-        last_obstacles[i] += shift as i64;
+        platform.rounds.push(rounds);
+        platform.cubes.push(cubes);
+        platform.segments.push(calculate_segments(cubes, platform.width));
     }
+
+    return platform;
 }
 
-fn move_up(matrix: &mut Matrix)
+fn move_vertical(platform: &mut Platform, direction: Direction)
 {
-    let mut last_obstacles: Vec<i64> = vec![-1; matrix[0].len()];
+    assert_ne!(direction, Direction::Left);
+    assert_ne!(direction, Direction::Right);
 
-    for i in 0..matrix.len()
+    let shift = convert_to_shift(direction);
+    let height = platform.rounds.len() as i64;
+
+    // Rows closest to the destination edge settle first, so each row only has to sink into settled ones
+    for step in 0..height
     {
-        for j in 0..matrix[i].len()
+        let i = if shift < 0 { step } else { height - 1 - step };
+
+        let mut moving: Row = platform.rounds[i as usize];
+        platform.rounds[i as usize] = 0;
+
+        let mut k = i;
+
+        while moving != 0 && k + shift >= 0 && k + shift < height
         {
-            calculate_vertical_change(matrix, i, j, &mut last_obstacles, Direction::Up);
+            let next = (k + shift) as usize;
+            let free: Row = moving & !(platform.rounds[next] | platform.cubes[next]);
+
+            platform.rounds[k as usize] |= moving & !free;
+            moving = free;
+            k += shift;
         }
+
+        platform.rounds[k as usize] |= moving;
     }
 }
 
-fn move_down(matrix: &mut Matrix)
+fn move_horizontal(platform: &mut Platform, direction: Direction)
 {
-    let mut last_obstacles: Vec<i64> = vec![matrix.len() as i64; matrix[0].len()];
+    assert_ne!(direction, Direction::Up);
+    assert_ne!(direction, Direction::Down);
+
+    let shift = convert_to_shift(direction);
 
-    for i in (0..matrix.len()).rev()
+    for i in 0..platform.rounds.len()
     {
-        for j in 0..matrix[i].len()
+        let mut row: Row = 0;
+
+        for segment in &platform.segments[i]
         {
-            calculate_vertical_change(matrix, i, j, &mut last_obstacles, Direction::Down);
+            let count = (platform.rounds[i] & segment.mask).count_ones() as usize;
+
+            if count == 0
+            {
+                continue;
+            }
+
+            let offset = if shift < 0 { segment.start } else { segment.start + segment.length - count };
+            row |= low_bits(count) << offset;
         }
+
+        platform.rounds[i] = row;
     }
 }
 
-fn move_left(matrix: &mut Matrix)
+fn tilt(platform: &mut Platform, direction: Direction)
 {
-    let mut last_obstacles: Vec<i64> = vec![-1 as i64; matrix.len()];
-
-    for i in 0..matrix.len()
+    match direction
     {
-        for j in 0..matrix[i].len()
-        {
-            calculate_horizontal_change(matrix, i, j, &mut last_obstacles, Direction::Left);
-        }
+        Direction::Up | Direction::Down => move_vertical(platform, direction),
+        Direction::Left | Direction::Right => move_horizontal(platform, direction)
     }
 }
 
-fn move_right(matrix: &mut Matrix)
+fn parse_sequence(sequence: &str) -> Result<Vec<Direction>, String>
 {
-    let mut last_obstacles: Vec<i64> = vec![matrix.len() as i64; matrix.len()];
-
-    for i in 0..matrix.len()
-    {
-        for j in (0..matrix[i].len()).rev()
-        {
-            calculate_horizontal_change(matrix, i, j, &mut last_obstacles, Direction::Right);
-        }
-    }
+    return sequence.chars().map(|c| Direction::from(c).map_err(|_| String::from("Tilt directions should be one of N, S, W or E"))).collect();
 }
 
-fn cycle(matrix: &mut Matrix)
+fn cycle(platform: &mut Platform, sequence: &Vec<Direction>)
 {
-    move_up(matrix);
-    move_left(matrix);
-    move_down(matrix);
-    move_right(matrix);
+    for direction in sequence
+    {
+        tilt(platform, *direction);
+    }
 }
 
-fn count_load(matrix: &Matrix) -> u64
+fn count_load(platform: &Platform, beam: Direction) -> u64
 {
     let mut load: u64 = 0;
+    let height = platform.rounds.len();
 
-    for i in 0..matrix.len()
+    for (i, row) in platform.rounds.iter().enumerate()
     {
-        for j in 0..matrix[i].len()
+        match beam
         {
-            if matrix[i][j] == Obstacle::Round
+            Direction::Up => load += row.count_ones() as u64 * (height - i) as u64,
+            Direction::Down => load += row.count_ones() as u64 * (i + 1) as u64,
+            Direction::Left | Direction::Right =>
             {
-                load += (matrix.len() - i) as u64;
+                let mut remaining: Row = *row;
+
+                while remaining != 0
+                {
+                    let j = remaining.trailing_zeros() as usize;
+                    load += if beam == Direction::Left { platform.width - j } else { j + 1 } as u64;
+                    remaining &= remaining - 1;
+                }
             }
         }
     }
@@ -192,82 +252,62 @@ fn count_load(matrix: &Matrix) -> u64
     return load;
 }
 
-fn get_configuration(matrix: &Matrix) -> Configuration
+fn spin(platform: &mut Platform, sequence: &Vec<Direction>, repetitions: u64)
 {
-    let mut configuration: Configuration = Configuration::new();
+    let mut seen_configurations: HashMap<Configuration, u64> = HashMap::new();
+    let mut history: Vec<Configuration> = Vec::new();
 
-    for i in 0..matrix.len()
+    for i in 0..repetitions
     {
-        for j in 0..matrix[i].len()
-        {
-            if matrix[i][j] == Obstacle::Round
-            {
-                configuration.push(Position{x: i, y: j});
-            }
-        }
-    }
+        let iteration_option = seen_configurations.get(&platform.rounds);
 
-    return configuration;
-}
+        if iteration_option.is_some()
+        {
+            let iteration = *iteration_option.unwrap();
+            let cycle_count = i - iteration;
+            let remaining_cyles = repetitions - iteration;
+            let final_cycle = remaining_cyles % cycle_count + iteration;
 
-fn clear_moveable_obstacles(matrix: &mut Matrix)
-{
-    for i in 0..matrix.len()
-    {
-        for j in 0..matrix[i].len()
+            platform.rounds = history[final_cycle as usize].clone();
+            break;
+        }
+        else
         {
-            if matrix[i][j] == Obstacle::Round
-            {
-                matrix[i][j] = Obstacle::None;
-            }
+            seen_configurations.insert(platform.rounds.clone(), i);
+            history.push(platform.rounds.clone());
+            cycle(platform, sequence);
         }
     }
 }
 
-fn setup_configuration(matrix: &mut Matrix, configuration: &Configuration)
+fn part1(platform: &mut Platform) -> u64
 {
-    clear_moveable_obstacles(matrix);
-
-    for configuration in configuration
-    {
-        matrix[configuration.x][configuration.y] = Obstacle::Round;
-    }
+    tilt(platform, Direction::Up);
+    return count_load(platform, Direction::Up);
 }
 
-fn part1(matrix: &mut Matrix) -> u64
+fn part2(platform: &mut Platform) -> u64
 {
-    move_up(matrix);
-    return count_load(matrix);
+    spin(platform, &parse_sequence(SPIN_CYCLE).unwrap(), NUMBER_CYCLES);
+    return count_load(platform, Direction::Up);
 }
 
-fn part2(matrix: &mut Matrix) -> u64
+const USAGE: &str = "Usage: <sequence> <repetitions> <beam>, e.g. \"NNES 1000 W\"";
+
+fn variant(platform: &mut Platform, args: &Vec<String>) -> Result<u64, String>
 {
-    let mut seen_configurations: HashMap<Configuration, u64> = HashMap::new();
+    let sequence = parse_sequence(&args[0])?;
+    let repetitions = args[1].parse::<u64>().map_err(|_| String::from("Repetitions should be a number"))?;
 
-    for i in 0..NUMBER_CYCLES
+    let mut beam_chars = args[2].chars();
+    let beam = match (beam_chars.next().map(Direction::from), beam_chars.next())
     {
-        let configuration: Configuration = get_configuration(matrix);
-        let iteration_option = seen_configurations.get(&configuration);
-
-        if iteration_option.is_some()
-        {
-            let iteration = iteration_option.unwrap();
-            let cycle_count = i - iteration;
-            let remaining_cyles = NUMBER_CYCLES - iteration;
-            let final_cycle = remaining_cyles % cycle_count + iteration;
+        (Some(Ok(beam)), None) => beam,
+        _ => return Err(String::from("Beam should be one of N, S, W or E"))
+    };
 
-            let final_configuration = seen_configurations.iter().find_map(|(key, &val)| if val == final_cycle { Some(key) } else { None }).unwrap();
-            setup_configuration(matrix, &final_configuration);
-            break;
-        }
-        else
-        {
-            seen_configurations.insert(configuration, i);
-            cycle(matrix);
-        }        
-    }
-    
-    return count_load(matrix);
+    spin(platform, &sequence, repetitions);
+    return Ok(count_load(platform, beam));
 }
 
 fn main()
@@ -275,11 +315,34 @@ fn main()
     let contents: String = fs::read_to_string(FILE_PATH)
         .expect("Should have been able to read the file");
 
-    let mut matrix: Matrix = parse(&contents);
+    let mut platform: Platform = parse(&contents);
+
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if !args.is_empty()
+    {
+        if args.len() != 3
+        {
+            eprintln!("{}", USAGE);
+            std::process::exit(1);
+        }
+
+        match variant(&mut platform, &args)
+        {
+            Ok(load) => println!("{}", load),
+            Err(message) =>
+            {
+                eprintln!("{}\n{}", message, USAGE);
+                std::process::exit(1);
+            }
+        }
+
+        return;
+    }
+
+    let part1_result: u64 = part1(&mut platform);
+    let part2_result: u64 = part2(&mut platform);
 
-    let part1_result: u64 = part1(&mut matrix);
-    let part2_result: u64 = part2(&mut matrix);
-    
     println!("{}", part1_result);
     println!("{}", part2_result);
 }
\ No newline at end of file
@@ -1,149 +1,121 @@
-#![feature(linked_list_remove)]
+use std::{fs, env};
 
-use std::{fs, collections::LinkedList, fmt::Display};
+use code_advent_2023::thashmap::{THashMap, THash, HolidayHash, DEFAULT_BUCKETS};
 
 const FILE_PATH : &str = "input.txt";
 
-type Bucket<TKey, TValue> = LinkedList<(TKey, TValue)>;
-type THashMapContainer<TKey, TValue> = [Bucket<TKey, TValue>; 256];
 type Data<'a> = Vec<&'a str>;
 
-#[derive(PartialEq, Eq)]
-enum Operation
-{
-    Insert,
-    Remove
-}
+type Boxes = THashMap<String, u64>;
 
-struct THashMap<TKey, TValue>
+#[derive(PartialEq, Eq, Debug)]
+enum Operation
 {
-    map: THashMapContainer<TKey, TValue>
+    Insert(String, u64),
+    Remove(String),
+    Lookup(String)
 }
 
-impl<TKey: std::fmt::Debug, TValue: std::fmt::Debug> Display for THashMap<TKey, TValue>
+impl Operation
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    // "label=focal" inserts or replaces a lens, "label-" removes it and "label?" reports where it sits
+    pub fn from(step: &str) -> Result<Operation, String>
     {
-        for (i, bucket) in self.map.iter().enumerate()
+        let step = step.trim();
+
+        if let Some((label, focal)) = step.split_once('=')
         {
-            if !bucket.is_empty()
-            {
-                writeln!(f, "{}: {:?}", i, bucket)?;
-            }
+            let focal_length = focal.parse::<u64>().map_err(|_| format!("Invalid focal length in \"{}\"", step))?;
+            return Ok(Operation::Insert(label.to_string(), focal_length));
         }
 
-        return Ok(());
-    }
-}
-
-trait THash
-{
-    fn hash(&self) -> u64;
-}
-
-impl THash for String
-{
-    fn hash(&self) -> u64
-    {
-        let mut result: u64 = 0;
+        if let Some(label) = step.strip_suffix('-')
+        {
+            return Ok(Operation::Remove(label.to_string()));
+        }
 
-        for c in self.chars()
+        if let Some(label) = step.strip_suffix('?')
         {
-            result = ((result + c as u64) * 17) % 256;
+            return Ok(Operation::Lookup(label.to_string()));
         }
 
-        return result;
+        return Err(format!("Unknown operation \"{}\"", step));
     }
 }
 
-trait Container<TKey, TValue>
+fn parse(contents: &String) -> Data
 {
-    fn insert(&mut self, key: &TKey, value: &TValue);
-    fn remove(&mut self, key: &TKey);
+    return contents.split(",").collect();
 }
 
-impl<TKey, TValue> Container<TKey, TValue> for THashMap<TKey, TValue>
-    where
-        TKey: THash + PartialEq<TKey> + Clone,
-        TValue: PartialEq<TValue> + Copy
+// Applies a single step, returning a report for lookups
+fn execute(hashmap: &mut Boxes, operation: &Operation) -> Option<String>
 {
-    fn insert(&mut self, key: &TKey, value: &TValue)
+    match operation
     {
-        let hash = key.hash();
-        let bucket = &mut self.map[hash as usize];
-
-        for it in bucket.iter_mut()
+        Operation::Insert(label, focal_length) =>
         {
-            if *key == it.0
-            {
-                it.1 = *value;
-                return;
-            }
-        }
-
-        bucket.push_back((key.clone(), *value));
-    }
-
-    fn remove(&mut self, key: &TKey)
-    {
-        let hash = key.hash();
-        let bucket = &mut self.map[hash as usize];
-
-        for (i, it) in bucket.iter_mut().enumerate()
+            hashmap.insert(label.clone(), *focal_length);
+            return None;
+        },
+        Operation::Remove(label) =>
+        {
+            hashmap.remove(label.as_str());
+            return None;
+        },
+        Operation::Lookup(label) =>
         {
-            if *key == it.0
+            return Some(match hashmap.locate(label.as_str())
             {
-                bucket.remove(i);
-                return;
-            }
+                Some((i, j)) =>
+                {
+                    let focal_length = hashmap.bucket(i)[j].1;
+                    format!("{}: box {}, slot {}, focal length {}, focusing power {}", label, i, j + 1, focal_length, ((i + 1) * (j + 1)) as u64 * focal_length)
+                },
+                None => format!("{}: not in any box (would go to box {})", label, HolidayHash.hash(label.as_str()) as usize % hashmap.bucket_count())
+            });
         }
     }
 }
 
-fn parse(contents: &String) -> Data
-{
-    return contents.split(",").collect();
-}
-
-fn check_operation(string: &str) -> Operation
+fn setup_hashmap(data: &Data, hashmap: &mut Boxes) -> Result<(), String>
 {
-    if string.contains(|c| c == '=')
+    for string in data
     {
-        return Operation::Insert;
+        execute(hashmap, &Operation::from(string)?);
     }
 
-    return Operation::Remove;
+    return Ok(());
 }
 
-fn setup_hashmap(data: &Data, hashmap: &mut THashMap<String, u64>)
+// Runs the sequence step by step, printing the boxes after every step in the puzzle's format
+fn trace(data: &Data) -> Result<u64, String>
 {
+    let mut hashmap: Boxes = THashMap::new(DEFAULT_BUCKETS);
+
     for string in data
     {
-        let operation = check_operation(string);
+        let operation = Operation::from(string)?;
+        let report_option = execute(&mut hashmap, &operation);
+
+        println!("After \"{}\":", string.trim());
 
-        match operation
+        if report_option.is_some()
         {
-            Operation::Insert =>
-            {
-                let key_value: Vec<&str> = string.split("=").collect();
-                let (key, value) = (key_value[0].to_string(), key_value[1].parse::<u64>().unwrap());
-                hashmap.insert(&key, &value);
-            },
-            Operation::Remove =>
-            {
-                let key_value: Vec<&str> = string.split("-").collect();
-                let key = key_value[0].to_string();
-                hashmap.remove(&key);
-            }
+            println!("{}", report_option.unwrap());
         }
+
+        println!("{}", hashmap);
     }
+
+    return Ok(calculate_power(&hashmap));
 }
 
-fn calculate_power(hashmap: &THashMap<String, u64>) -> u64
+fn calculate_power(hashmap: &Boxes) -> u64
 {
     let mut result = 0;
 
-    for (i, bucket) in hashmap.map.iter().enumerate()
+    for (i, bucket) in hashmap.buckets()
     {
         for (j, item) in bucket.iter().enumerate()
         {
@@ -160,19 +132,18 @@ fn part1(data: &Data) -> u64
 
     for string in data
     {
-        result += string.to_string().hash();
+        result += HolidayHash.hash(*string);
     }
 
     return result;
 }
 
-fn part2(data: &Data) -> u64
+fn part2(data: &Data) -> Result<u64, String>
 {
-    const BUCKET: Bucket<String, u64> = Bucket::new();
-    let mut hashmap: THashMap<String, u64> = THashMap{map: [BUCKET; 256] };
+    let mut hashmap: Boxes = THashMap::new(DEFAULT_BUCKETS);
 
-    setup_hashmap(data, &mut hashmap);
-    return calculate_power(&hashmap);
+    setup_hashmap(data, &mut hashmap)?;
+    return Ok(calculate_power(&hashmap));
 }
 
 fn main()
@@ -182,8 +153,32 @@ fn main()
 
     let data: Data = parse(&contents);
 
+    // Usage: trace, to follow the initialization sequence one step at a time
+    if env::args().nth(1).as_deref() == Some("trace")
+    {
+        match trace(&data)
+        {
+            Ok(power) => println!("Focusing power: {}", power),
+            Err(message) =>
+            {
+                eprintln!("{}", message);
+                std::process::exit(1);
+            }
+        }
+
+        return;
+    }
+
     let part1_result: u64 = part1(&data);
-    let part2_result: u64 = part2(&data);
+    let part2_result: u64 = match part2(&data)
+    {
+        Ok(result) => result,
+        Err(message) =>
+        {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
     
     println!("{}", part1_result);
     println!("{}", part2_result);
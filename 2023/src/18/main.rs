@@ -1,6 +1,6 @@
 use std::{fs, ops::{Add, AddAssign, Mul}};
 
-use num::{abs, integer::sqrt, pow};
+use code_advent_2023::polygon::Polygon;
 
 const FILE_PATH : &str = "input.txt";
 
@@ -147,52 +147,10 @@ fn get_vertices<TPart : GetVertex>(contents: &String) -> Vertices
     return vertices;
 }
 
-fn determinant(pos1: &Position, pos2: &Position) -> i64
-{
-    return pos1.x * pos2.y - pos1.y * pos2.x;
-}
-
-fn calculate_area(positions: &Vertices) -> u64
-{
-    let mut area = 0;
-
-    for i in 1..positions.len()
-    {
-        area += determinant(&positions[i-1], &positions[i]);
-    }
-
-    return abs(area / 2) as u64;
-}
-
-fn distance(pos1: &Position, pos2: &Position) -> u64
-{
-    return sqrt((pow(pos2.x - pos1.x, 2) + pow(pos2.y - pos1.y, 2)).try_into().unwrap());
-}
-
-fn calculate_points_along_vertices(vertices: &Vertices) -> u64
-{
-    let mut points: u64 = 0;
-
-    for i in 1..vertices.len()
-    {
-        points += distance(&vertices[i - 1], &vertices[i]);
-    }
-
-    return points;
-}
-
-fn calculate_pick(area: u64, number_of_vertices: u64) -> u64
-{
-    return area - (number_of_vertices / 2) + 1;
-}
-
 fn calculate_total(vertices: &Vertices) -> u64
 {
-    let points = calculate_points_along_vertices(&vertices);
-    let area = calculate_area(&vertices);
-    let pick = calculate_pick(area, points);
-
-    return points + pick;
+    let polygon: Polygon = Polygon::new(vertices.iter().map(|vertex| (vertex.x, vertex.y)).collect());
+    return (polygon.boundary_points() + polygon.interior_points()) as u64;
 }
 
 fn part1(contents: &String) -> u64
@@ -1,8 +1,12 @@
-use std::{fs, collections::{VecDeque, HashSet}, ops::{Add, AddAssign}};
+use std::{fs, env, collections::VecDeque, ops::{Add, AddAssign}};
+
+use num::integer::lcm;
 
 const FILE_PATH : &str = "input.txt";
 const PART1_STEPS: u64 = 64;
 const PART2_STEPS: u64 = 26501365;
+// Consecutive equal second differences needed before trusting the quadratic growth
+const STABLE_PERIODS: u64 = 3;
 
 type Neighbors = Vec<Position>;
 type Row = Vec<Obstacle>;
@@ -31,12 +35,6 @@ struct Position
     y: i64
 }
 
-struct Node
-{
-    position: Position,
-    steps: u64
-}
-
 impl Add for Position
 {
     type Output = Self;
@@ -63,32 +61,90 @@ impl AddAssign for Position
     }
 }
 
-struct Part1;
-struct Part2;
-
-trait IsValid
+// Shortest distances from a start over the garden repeated `tiles` times in every direction
+struct DistanceField
 {
-    fn is_valid(position: &Position, matrix: &Matrix) -> bool;
+    at: Vec<u64>,
+    within: Vec<u64>
 }
 
-impl IsValid for Part1
+impl DistanceField
 {
-    fn is_valid(position: &Position, matrix: &Matrix) -> bool
+    fn new(matrix: &Matrix, start: &Position, tiles: u64) -> DistanceField
     {
-        let in_bounds: bool = (position.x >= 0 && position.x < matrix.len() as i64)
-                            && (position.y >= 0 && position.y < matrix[0].len() as i64);
-        
-        return in_bounds && matrix[position.x as usize][position.y as usize] != Obstacle::Rock;
+        let size: Position = Position{x: matrix.len() as i64, y: matrix[0].len() as i64};
+        let repeats: i64 = 2 * tiles as i64 + 1;
+        let extent: Position = Position{x: size.x * repeats, y: size.y * repeats};
+        let origin: Position = *start + Position{x: size.x * tiles as i64, y: size.y * tiles as i64};
+
+        let mut distances: Vec<Option<u64>> = vec![None; (extent.x * extent.y) as usize];
+        let mut counts: Vec<u64> = Vec::new();
+        let mut positions: VecDeque<Position> = vec![origin].into();
+
+        distances[(origin.x * extent.y + origin.y) as usize] = Some(0);
+
+        while !positions.is_empty()
+        {
+            let position: Position = positions.pop_front().unwrap();
+            let distance: u64 = distances[(position.x * extent.y + position.y) as usize].unwrap();
+
+            if counts.len() as u64 == distance
+            {
+                counts.push(0);
+            }
+
+            counts[distance as usize] += 1;
+
+            for neighbor in get_neighbors(matrix, &position, &extent)
+            {
+                let index = (neighbor.x * extent.y + neighbor.y) as usize;
+
+                if distances[index].is_none()
+                {
+                    distances[index] = Some(distance + 1);
+                    positions.push_back(neighbor);
+                }
+            }
+        }
+
+        let mut at: Vec<u64> = Vec::new();
+        let mut within: Vec<u64> = Vec::new();
+
+        for (distance, count) in counts.iter().enumerate()
+        {
+            at.push(count + if distance >= 2 { at[distance - 2] } else { 0 });
+            within.push(count + if distance >= 1 { within[distance - 1] } else { 0 });
+        }
+
+        return DistanceField{at: at, within: within};
     }
-}
 
-impl IsValid for Part2
-{
-    fn is_valid(position: &Position, matrix: &Matrix) -> bool
+    fn furthest(&self) -> u64
+    {
+        return self.within.len() as u64 - 1;
+    }
+
+    // Positions whose distance has the same parity as steps, since those can be reached by stepping back and forth
+    fn reachable_at(&self, steps: u64) -> u64
+    {
+        let furthest = self.furthest();
+
+        if steps <= furthest
+        {
+            return self.at[steps as usize];
+        }
+
+        if (steps - furthest) % 2 == 0
+        {
+            return self.at[furthest as usize];
+        }
+
+        return if furthest > 0 { self.at[furthest as usize - 1] } else { 0 };
+    }
+
+    fn reachable_within(&self, steps: u64) -> u64
     {
-        let modulo_x = position.x.rem_euclid(matrix.len() as i64);
-        let modulo_y = position.y.rem_euclid(matrix[0].len() as i64);
-        return matrix[modulo_x as usize][modulo_y as usize] != Obstacle::Rock;
+        return self.within[steps.min(self.furthest()) as usize];
     }
 }
 
@@ -141,15 +197,18 @@ fn find_start(contents: &String) -> Position
     return Position{x: 0, y: 0};
 }
 
-fn get_neighbors<TPart: IsValid>(matrix: &Matrix, position: &Position) -> Neighbors
+fn get_neighbors(matrix: &Matrix, position: &Position, extent: &Position) -> Neighbors
 {
     let directions: Vec<Direction> = vec![Direction::Up, Direction::Left, Direction::Down, Direction::Right];
     let mut neighbors: Neighbors = Neighbors::new();
 
     for direction in directions
     {
-        let new_position: Position = *position + convert_to_coordinates(direction);        
-        if TPart::is_valid(&new_position, &matrix)
+        let new_position: Position = *position + convert_to_coordinates(direction);
+        let in_bounds: bool = (new_position.x >= 0 && new_position.x < extent.x)
+                            && (new_position.y >= 0 && new_position.y < extent.y);
+
+        if in_bounds && matrix[new_position.x as usize % matrix.len()][new_position.y as usize % matrix[0].len()] != Obstacle::Rock
         {
             neighbors.push(new_position);
         }
@@ -158,40 +217,6 @@ fn get_neighbors<TPart: IsValid>(matrix: &Matrix, position: &Position) -> Neighb
     return neighbors;
 }
 
-fn fill<TPart: IsValid>(matrix: &Matrix, start: &Position, steps: u64) -> u64
-{
-    type Positions = HashSet<Position>;
-
-    let mut end_positions: Positions = Positions::new();
-    let mut seen: Positions = Positions::new();
-    let mut nodes: VecDeque<Node> = vec![Node{position: *start, steps: steps}].into();
-
-    while !nodes.is_empty()
-    {
-        let node: Node = nodes.pop_front().unwrap();
-
-        if node.steps % 2 == 0
-        {
-            end_positions.insert(node.position);
-        }
-
-        if node.steps != 0
-        {
-            let neighbors = get_neighbors::<TPart>(&matrix, &node.position);
-            for neighbor in neighbors
-            {
-                if !seen.contains(&neighbor)
-                {
-                    seen.insert(neighbor);
-                    nodes.push_back(Node{position: neighbor, steps: node.steps - 1});
-                }
-            }
-        }
-    }
-
-    return end_positions.len() as u64;
-}
-
 fn langrange_polynomial(quadratic_points: &Vec<Position>, steps: u64) -> u64
 {
     let mut result = 0;
@@ -222,23 +247,183 @@ fn langrange_polynomial(quadratic_points: &Vec<Position>, steps: u64) -> u64
 
 fn part1(matrix: &Matrix, start: &Position) -> u64
 {
-    return fill::<Part1>(matrix, start, PART1_STEPS);
+    return DistanceField::new(matrix, start, 0).reachable_at(PART1_STEPS);
+}
+
+fn check_assumptions(matrix: &Matrix, start: &Position, steps: u64) -> Result<(), String>
+{
+    let size = matrix.len() as i64;
+    let half = size / 2;
+
+    if matrix.iter().any(|row| row.len() as i64 != size)
+    {
+        return Err(String::from("the garden is not square"));
+    }
+
+    if size % 2 == 0
+    {
+        return Err(format!("the garden has even size {}", size));
+    }
+
+    if start.x != half || start.y != half
+    {
+        return Err(format!("the start ({}, {}) is not at the centre of the garden", start.x, start.y));
+    }
+
+    for i in 0..size as usize
+    {
+        if matrix[half as usize][i] == Obstacle::Rock || matrix[i][half as usize] == Obstacle::Rock
+        {
+            return Err(String::from("the start row or column contains rocks"));
+        }
+
+        if matrix[0][i] == Obstacle::Rock || matrix[size as usize - 1][i] == Obstacle::Rock
+            || matrix[i][0] == Obstacle::Rock || matrix[i][size as usize - 1] == Obstacle::Rock
+        {
+            return Err(String::from("the border of the garden contains rocks"));
+        }
+    }
+
+    if steps < size as u64 || steps % size as u64 != half as u64
+    {
+        return Err(format!("{} steps is not {} plus a positive multiple of {}", steps, half, size));
+    }
+
+    return Ok(());
+}
+
+// The reachable area is a diamond of whole tiles, capped by 4 corner tiles and with 2 kinds of partial
+// tiles along each diagonal edge. Whole tiles alternate parity since the garden size is odd
+fn diamond(matrix: &Matrix, start: &Position, steps: u64) -> u64
+{
+    let size = matrix.len() as i64;
+    let half = size / 2;
+    let last = size - 1;
+    let radius = steps / size as u64 - 1;
+
+    let same_parity_tiles = (radius / 2 * 2 + 1).pow(2);
+    let other_parity_tiles = ((radius + 1) / 2 * 2).pow(2);
+    let centre: DistanceField = DistanceField::new(matrix, start, 0);
+    let same_parity_points = centre.reachable_at(2 * size as u64 + steps % 2);
+    let other_parity_points = centre.reachable_at(2 * size as u64 + 1 - steps % 2);
+
+    let mut corners: u64 = 0;
+
+    for entry in [Position{x: last, y: half}, Position{x: half, y: 0}, Position{x: 0, y: half}, Position{x: half, y: last}]
+    {
+        corners += DistanceField::new(matrix, &entry, 0).reachable_at(last as u64);
+    }
+
+    let mut small_edges: u64 = 0;
+    let mut large_edges: u64 = 0;
+
+    for entry in [Position{x: last, y: 0}, Position{x: last, y: last}, Position{x: 0, y: 0}, Position{x: 0, y: last}]
+    {
+        let field: DistanceField = DistanceField::new(matrix, &entry, 0);
+        small_edges += field.reachable_at(half as u64 - 1);
+        large_edges += field.reachable_at((3 * size / 2 - 1) as u64);
+    }
+
+    return same_parity_tiles * same_parity_points
+        + other_parity_tiles * other_parity_points
+        + corners
+        + (radius + 1) * small_edges
+        + radius * large_edges;
 }
 
 // Idea gotten from https://www.reddit.com/r/adventofcode/comments/18nevo3/comment/kee6vn6/
-fn part2(matrix: &Matrix, start: &Position) -> u64
+fn interpolate(matrix: &Matrix, start: &Position, steps: u64) -> u64
 {
     let size: Position = Position{x: matrix.len() as i64, y: matrix[0].len() as i64};
+    let field: DistanceField = DistanceField::new(matrix, start, 2);
     let mut quadratic_points: Vec<Position> = Vec::new();
 
     for i in 0..3
     {
         let x: u64 = ((size.x / 2) + (i * size.x)) as u64;
-        let y: u64 = fill::<Part2>(matrix, start, x);
+        let y: u64 = field.reachable_at(x);
         quadratic_points.push(Position{x: x as i64, y: y as i64});
     }
 
-    return langrange_polynomial(&quadratic_points, PART2_STEPS);
+    return langrange_polynomial(&quadratic_points, steps);
+}
+
+// Once the reachable area spans enough gardens, the count at steps r + k * period grows quadratically in k.
+// Keep widening the walk until the last few second differences agree, then extend with finite differences.
+// Nothing checks the result, so it is only offered on request for gardens the diamond can't handle
+fn extrapolate(matrix: &Matrix, start: &Position, steps: u64) -> u64
+{
+    let (rows, columns) = (matrix.len() as u64, matrix[0].len() as u64);
+    let period = lcm(rows, columns);
+    let (remainder, repeats) = (steps % period, steps / period);
+    let mut periods: u64 = STABLE_PERIODS + 1;
+
+    loop
+    {
+        let tiles = (remainder + periods * period) / rows.min(columns) + 2;
+        let field: DistanceField = DistanceField::new(matrix, start, tiles);
+
+        if repeats <= periods
+        {
+            return field.reachable_at(steps);
+        }
+
+        let samples: Vec<i128> = (0..=periods).map(|k| field.reachable_at(remainder + k * period) as i128).collect();
+        let second_differences: Vec<i128> = samples.windows(3).map(|window| window[2] - 2 * window[1] + window[0]).collect();
+        let settled = &second_differences[second_differences.len() - STABLE_PERIODS as usize..];
+
+        if settled.iter().all(|difference| *difference == settled[0])
+        {
+            let base = (periods - 2) as usize;
+            let first_difference = samples[base + 1] - samples[base];
+            let k = (repeats - periods + 2) as i128;
+
+            return (samples[base] + k * first_difference + k * (k - 1) / 2 * settled[0]) as u64;
+        }
+
+        periods *= 2;
+    }
+}
+
+// The diamond decomposition only holds for gardens shaped like the puzzle input, and is checked against the polynomial
+fn part2(matrix: &Matrix, start: &Position) -> Result<u64, String>
+{
+    check_assumptions(matrix, start, PART2_STEPS)?;
+
+    let result: u64 = diamond(matrix, start, PART2_STEPS);
+    let polynomial: u64 = interpolate(matrix, start, PART2_STEPS);
+
+    if result != polynomial
+    {
+        return Err(format!("the diamond gives {} but the polynomial gives {}", result, polynomial));
+    }
+
+    return Ok(result);
+}
+
+const USAGE: &str = "Usage: <steps> [tiles], e.g. \"500 4\", or extrapolate <steps> for an unverified estimate on gardens that don't fit the diamond";
+
+fn number(args: &Vec<String>, n: usize) -> Result<u64, String>
+{
+    let arg = args.get(n).ok_or(format!("Missing argument {}", n))?;
+    return arg.parse::<u64>().map_err(|_| format!("Argument \"{}\" should be a number", arg));
+}
+
+fn explore(matrix: &Matrix, start: &Position, args: &Vec<String>) -> Result<(), String>
+{
+    if args[0] == "extrapolate"
+    {
+        println!("{} (unverified)", extrapolate(matrix, start, number(args, 1)?));
+        return Ok(());
+    }
+
+    let steps = number(args, 0)?;
+    let tiles = if args.len() > 1 { number(args, 1)? } else { 0 };
+    let field: DistanceField = DistanceField::new(matrix, start, tiles);
+
+    println!("{}", field.reachable_at(steps));
+    println!("{}", field.reachable_within(steps));
+    return Ok(());
 }
 
 fn main()
@@ -249,8 +434,29 @@ fn main()
     let matrix: Matrix = parse(&contents);
     let start: Position = find_start(&contents);
 
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if !args.is_empty()
+    {
+        if let Err(message) = explore(&matrix, &start, &args)
+        {
+            eprintln!("{}\n{}", message, USAGE);
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
     let part1_result: u64 = part1(&matrix, &start);
-    let part2_result: u64 = part2(&matrix, &start);
+    let part2_result: u64 = match part2(&matrix, &start)
+    {
+        Ok(result) => result,
+        Err(reason) =>
+        {
+            eprintln!("Can't extrapolate the infinite garden: {}\nTry \"extrapolate {}\" for an unverified estimate", reason, PART2_STEPS);
+            std::process::exit(1);
+        }
+    };
     
     println!("{}", part1_result);
     println!("{}", part2_result);
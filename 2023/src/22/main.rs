@@ -1,11 +1,14 @@
 // Idea for part 1 from https://github.com/hyper-neutrino/advent-of-code/blob/main/2023/day22p1.py
-// Idea for part 2 from https://github.com/hyper-neutrino/advent-of-code/blob/main/2023/day22p2.py
 
-use std::{fs, cmp::{max, min}, cell::RefCell, collections::{VecDeque, HashSet}};
+mod stack;
+
+use std::{fs, env};
+
+use stack::Stack;
 
 const FILE_PATH : &str = "input.txt";
 
-type Bricks = Vec<RefCell<Brick>>;
+type Bricks = Vec<Brick>;
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 struct Position
@@ -18,6 +21,8 @@ struct Position
 #[derive(Debug)]
 struct Brick
 {
+    // Line of the brick in the input, which stays put while bricks get sorted
+    id: usize,
     start: Position,
     end: Position,
     supports: Vec<usize>,
@@ -28,7 +33,7 @@ fn parse(contents: &String) -> Bricks
 {
     let mut bricks: Bricks = Bricks::new();
 
-    for line in contents.lines()
+    for (id, line) in contents.lines().enumerate()
     {
         let coordinates_str: Vec<&str> = line.split("~").collect();
         let (start_str, end_str) = (coordinates_str[0], coordinates_str[1]);
@@ -40,142 +45,181 @@ fn parse(contents: &String) -> Bricks
 
         bricks.push(Brick
             {
+                id: id,
                 start: start_pos,
                 end: end_pos,
                 supports: Vec::new(),
                 is_supported: Vec::new()
-            }.into());
+            });
     }
 
-    bricks.sort_by_key(|brick| brick.borrow().start.z);
+    bricks.sort_by_key(|brick| brick.start.z);
 
     return bricks;
 }
 
-fn overlaps(lhs: &Brick, rhs: &Brick) -> bool
+// Top of the highest settled cube over each (x, y) column, along with the brick it belongs to
+struct HeightMap
 {
-    return max(lhs.start.x, rhs.start.x) <= min(lhs.end.x, rhs.end.x)
-        && max(lhs.start.y, rhs.start.y) <= min(lhs.end.y, rhs.end.y);
+    width: usize,
+    cells: Vec<(i64, Option<usize>)>
 }
 
-fn fall_bricks(bricks: &mut Bricks)
+impl HeightMap
 {
-    for i in 0..bricks.len()
+    pub fn new(bricks: &Bricks) -> HeightMap
+    {
+        let width = bricks.iter().map(|brick| brick.end.x).max().unwrap_or(0) as usize + 1;
+        let depth = bricks.iter().map(|brick| brick.end.y).max().unwrap_or(0) as usize + 1;
+
+        return HeightMap{width: width, cells: vec![(0, None); width * depth]};
+    }
+
+    fn footprint(&self, brick: &Brick) -> Vec<usize>
     {
-        let mut max_z = 1;
+        let mut cells: Vec<usize> = Vec::new();
 
-        let mut brick = bricks[i].borrow_mut();
-        for j in 0..i
+        for y in brick.start.y..=brick.end.y
         {
-            let other_brick = bricks[j].borrow();
-            if overlaps(&brick, &other_brick)
+            for x in brick.start.x..=brick.end.x
             {
-                max_z = max(max_z, other_brick.end.z + 1);
+                cells.push(y as usize * self.width + x as usize);
             }
         }
 
-        brick.end.z -= brick.start.z - max_z;
-        brick.start.z = max_z;
+        return cells;
     }
-
-    bricks.sort_by_key(|brick| brick.borrow().start.z);
 }
 
-fn fill_support(bricks: &mut Bricks)
+// Drops the bricks lowest first onto the height map, recording who rests on whom as they land.
+// Bricks only ever rest on bricks settled before them, so the order is topological for the support graph
+fn settle(bricks: &mut Bricks)
 {
+    let mut height_map: HeightMap = HeightMap::new(bricks);
+
     for i in 0..bricks.len()
     {
-        let mut upper_brick = bricks[i].borrow_mut();
-        for j in 0..i
+        let cells = height_map.footprint(&bricks[i]);
+        let floor = cells.iter().map(|&cell| height_map.cells[cell].0).max().unwrap();
+
+        let mut is_supported: Vec<usize> = cells.iter()
+            .filter(|&&cell| height_map.cells[cell].0 == floor)
+            .filter_map(|&cell| height_map.cells[cell].1)
+            .collect();
+        is_supported.sort();
+        is_supported.dedup();
+
+        let brick = &mut bricks[i];
+        brick.end.z -= brick.start.z - (floor + 1);
+        brick.start.z = floor + 1;
+        brick.is_supported = is_supported;
+
+        for &cell in &cells
         {
-            let mut lower_brick = bricks[j].borrow_mut();
-            if overlaps(&upper_brick, &lower_brick) && upper_brick.start.z == lower_brick.end.z + 1
-            {
-                lower_brick.supports.push(i);
-                upper_brick.is_supported.push(j);
-            }
+            height_map.cells[cell] = (brick.end.z, Some(i));
         }
 
+        for j in bricks[i].is_supported.clone()
+        {
+            bricks[j].supports.push(i);
+        }
     }
 }
 
-fn part1(bricks: &Bricks) -> u64
+// Dominator tree over the support graph with the ground as root: a brick falls when any of its
+// dominators is removed, so each subtree holds exactly the bricks that fall along with its root.
+// Node 0 is the ground and brick i is node i + 1
+struct Dominators
 {
-    let mut result: u64 = 0;
+    ancestors: Vec<Vec<usize>>,
+    depth: Vec<usize>,
+    subtree: Vec<u64>
+}
 
-    for brick in bricks
+impl Dominators
+{
+    fn parent(&self, node: usize) -> usize
     {
-        let are_bricks_supported = brick.borrow().supports.iter().all(|id|
-            {
-                let other_brick = bricks[*id].borrow();
-                return other_brick.is_supported.len() > 1;
-            });
+        return self.ancestors[node][0];
+    }
 
-        if are_bricks_supported
+    fn lca(&self, mut lhs: usize, mut rhs: usize) -> usize
+    {
+        if self.depth[lhs] < self.depth[rhs]
         {
-            result += 1;
+            std::mem::swap(&mut lhs, &mut rhs);
         }
-    }
 
-    return result;
-}
+        for level in (0..self.ancestors[lhs].len()).rev()
+        {
+            if self.depth[lhs] - self.depth[rhs] >= 1 << level
+            {
+                lhs = self.ancestors[lhs][level];
+            }
+        }
 
-fn fill_deque(bricks: &Bricks, brick: &Brick) -> VecDeque<usize>
-{
-    let mut deque: VecDeque<usize> = VecDeque::new();
+        if lhs == rhs
+        {
+            return lhs;
+        }
 
-    for id in brick.supports.iter()
-    {
-        let other_brick = bricks[*id].borrow();
-        if other_brick.is_supported.len() == 1
+        for level in (0..self.ancestors[lhs].len()).rev()
         {
-            deque.push_back(*id);
+            if self.ancestors[lhs][level] != self.ancestors[rhs][level]
+            {
+                lhs = self.ancestors[lhs][level];
+                rhs = self.ancestors[rhs][level];
+            }
         }
-    }
 
-    return deque;
-}
+        return self.parent(lhs);
+    }
 
-fn disintegrate(bricks: &Bricks, only_supported_by_one: &mut VecDeque<usize>, falling: &mut HashSet<usize>)
-{
-    while !only_supported_by_one.is_empty()
+    // On a DAG visited in topological order, the immediate dominator is the common dominator of all predecessors
+    pub fn new(bricks: &Bricks) -> Dominators
     {
-        let id = only_supported_by_one.pop_front().unwrap();
-        let brick = bricks[id].borrow();
+        let count = bricks.len() + 1;
+        let levels = (usize::BITS - count.leading_zeros()) as usize;
+        let mut dominators = Dominators{ancestors: vec![vec![0; levels]; count], depth: vec![0; count], subtree: vec![1; count]};
 
-        for i in brick.supports.iter()
+        for (i, brick) in bricks.iter().enumerate()
         {
-            if !falling.contains(i)
+            let node = i + 1;
+            let parent = brick.is_supported.iter().map(|&j| j + 1).reduce(|lhs, rhs| dominators.lca(lhs, rhs)).unwrap_or(0);
+
+            dominators.depth[node] = dominators.depth[parent] + 1;
+            dominators.ancestors[node][0] = parent;
+
+            for level in 1..levels
             {
-                let supported_brick = bricks[*i].borrow();
-                if supported_brick.is_supported.iter().all(|brick| falling.contains(brick))
-                {
-                    only_supported_by_one.push_back(*i);
-                    falling.insert(*i);
-                }
+                dominators.ancestors[node][level] = dominators.ancestors[dominators.ancestors[node][level - 1]][level - 1];
             }
         }
-    }
-}
 
-fn part2(bricks: &Bricks) -> u64
-{
-    let mut result: u64 = 0;
-    
-    for i in 0..bricks.len()
-    {
-        let brick = bricks[i].borrow();
+        for node in (1..count).rev()
+        {
+            let parent = dominators.parent(node);
+            dominators.subtree[parent] += dominators.subtree[node];
+        }
 
-        let mut only_supported_by_one: VecDeque<usize> = fill_deque(&bricks, &brick);
-        let mut falling: HashSet<usize> = HashSet::from_iter(only_supported_by_one.clone());
-        falling.insert(i);
+        return dominators;
+    }
 
-        disintegrate(&bricks, &mut only_supported_by_one, &mut falling);
-        
-        result += falling.len() as u64 - 1;
+    // Other bricks that fall when brick i is removed
+    pub fn falling(&self, i: usize) -> u64
+    {
+        return self.subtree[i + 1] - 1;
     }
+}
+
+fn part1(bricks: &Bricks, dominators: &Dominators) -> u64
+{
+    return (0..bricks.len()).filter(|&i| dominators.falling(i) == 0).count() as u64;
+}
 
-    return result;
+fn part2(bricks: &Bricks, dominators: &Dominators) -> u64
+{
+    return (0..bricks.len()).map(|i| dominators.falling(i)).sum();
 }
 
 fn main()
@@ -184,11 +228,29 @@ fn main()
         .expect("Should have been able to read the file");
 
     let mut bricks: Bricks = parse(&contents);
-    fall_bricks(&mut bricks);
-    fill_support(&mut bricks);
+    settle(&mut bricks);
+
+    let dominators: Dominators = Dominators::new(&bricks);
+
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if !args.is_empty()
+    {
+        match stack::explore(&Stack::new(&bricks, &dominators), &args)
+        {
+            Ok(output) => println!("{}", output),
+            Err(message) =>
+            {
+                eprintln!("{}", message);
+                std::process::exit(1);
+            }
+        }
+
+        return;
+    }
 
-    let part1_result: u64 = part1(&bricks);
-    let part2_result: u64 = part2(&bricks);
+    let part1_result: u64 = part1(&bricks, &dominators);
+    let part2_result: u64 = part2(&bricks, &dominators);
     
     println!("{}", part1_result);
     println!("{}", part2_result);
@@ -0,0 +1,158 @@
+use std::fs;
+
+use super::{Bricks, Dominators, Position};
+
+// Queries over the settled bricks. Bricks are named by their input line, starting at 0
+pub struct Stack<'a>
+{
+    bricks: &'a Bricks,
+    dominators: &'a Dominators,
+    indices: Vec<usize>
+}
+
+// Corners of a unit cube and its faces as quads over them, counter-clockwise seen from outside
+const CORNERS: [(i64, i64, i64); 8] = [(0, 0, 0), (1, 0, 0), (1, 1, 0), (0, 1, 0), (0, 0, 1), (1, 0, 1), (1, 1, 1), (0, 1, 1)];
+const FACES: [[usize; 4]; 6] = [[0, 3, 2, 1], [4, 5, 6, 7], [0, 1, 5, 4], [2, 3, 7, 6], [1, 2, 6, 5], [0, 4, 7, 3]];
+
+impl<'a> Stack<'a>
+{
+    pub fn new(bricks: &'a Bricks, dominators: &'a Dominators) -> Stack<'a>
+    {
+        let mut indices: Vec<usize> = vec![0; bricks.len()];
+
+        for (i, brick) in bricks.iter().enumerate()
+        {
+            indices[brick.id] = i;
+        }
+
+        return Stack{bricks: bricks, dominators: dominators, indices: indices};
+    }
+
+    fn index(&self, id: usize) -> Result<usize, String>
+    {
+        return self.indices.get(id).copied().ok_or(format!("There is no brick {}, only {}", id, self.bricks.len()));
+    }
+
+    fn ids(&self, indices: &Vec<usize>) -> Vec<usize>
+    {
+        let mut ids: Vec<usize> = indices.iter().map(|&i| self.bricks[i].id).collect();
+        ids.sort();
+        return ids;
+    }
+
+    pub fn resting_on(&self, id: usize) -> Result<Vec<usize>, String>
+    {
+        return Ok(self.ids(&self.bricks[self.index(id)?].supports));
+    }
+
+    // Bricks settle in topological order, so one pass sees every supporter's fate before the bricks on top
+    pub fn falling(&self, removed: &Vec<usize>) -> Result<Vec<usize>, String>
+    {
+        let mut gone: Vec<bool> = vec![false; self.bricks.len()];
+        let mut falling: Vec<usize> = Vec::new();
+
+        for id in removed
+        {
+            gone[self.index(*id)?] = true;
+        }
+
+        for (i, brick) in self.bricks.iter().enumerate()
+        {
+            if !gone[i] && !brick.is_supported.is_empty() && brick.is_supported.iter().all(|&j| gone[j])
+            {
+                gone[i] = true;
+                falling.push(i);
+            }
+        }
+
+        return Ok(self.ids(&falling));
+    }
+
+    pub fn safe(&self) -> Vec<usize>
+    {
+        return self.ids(&(0..self.bricks.len()).filter(|&i| self.dominators.falling(i) == 0).collect());
+    }
+
+    pub fn settled(&self, id: usize) -> Result<(Position, Position), String>
+    {
+        let brick = &self.bricks[self.index(id)?];
+        return Ok((brick.start, brick.end));
+    }
+
+    // One object per brick, made of a unit cube per voxel
+    pub fn to_obj(&self) -> String
+    {
+        let mut lines: Vec<String> = Vec::new();
+        let mut vertices: usize = 0;
+
+        for id in 0..self.bricks.len()
+        {
+            let brick = &self.bricks[self.indices[id]];
+            lines.push(format!("o brick_{}", id));
+
+            for z in brick.start.z..=brick.end.z
+            {
+                for y in brick.start.y..=brick.end.y
+                {
+                    for x in brick.start.x..=brick.end.x
+                    {
+                        for (dx, dy, dz) in CORNERS
+                        {
+                            lines.push(format!("v {} {} {}", x + dx, y + dy, z + dz));
+                        }
+
+                        for face in FACES
+                        {
+                            lines.push(format!("f {}", face.iter().map(|corner| (vertices + corner + 1).to_string()).collect::<Vec<String>>().join(" ")));
+                        }
+
+                        vertices += CORNERS.len();
+                    }
+                }
+            }
+        }
+
+        return lines.join("\n");
+    }
+}
+
+fn parse_id(id_str: &str) -> Result<usize, String>
+{
+    return id_str.trim().parse::<usize>().map_err(|_| format!("Invalid brick \"{}\"", id_str));
+}
+
+fn format_ids(ids: &Vec<usize>) -> String
+{
+    return ids.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+}
+
+// Usage: on <brick> | fall <brick,brick,...> | safe | coords | obj <path>
+pub fn explore(stack: &Stack, args: &Vec<String>) -> Result<String, String>
+{
+    let argument = || args.get(1).ok_or(format!("Mode {} needs an argument", args[0]));
+
+    return match args[0].as_str()
+    {
+        "on" => Ok(format_ids(&stack.resting_on(parse_id(argument()?)?)?)),
+        "fall" =>
+        {
+            let removed = argument()?.split(",").map(parse_id).collect::<Result<Vec<usize>, String>>()?;
+            Ok(format_ids(&stack.falling(&removed)?))
+        }
+        "safe" => Ok(format_ids(&stack.safe())),
+        "coords" =>
+        {
+            let lines = (0..stack.bricks.len())
+                .map(|id| stack.settled(id).map(|(start, end)| format!("{},{},{}~{},{},{}", start.x, start.y, start.z, end.x, end.y, end.z)))
+                .collect::<Result<Vec<String>, String>>()?;
+            Ok(lines.join("\n"))
+        }
+        "obj" =>
+        {
+            let path = argument()?;
+            fs::write(path, stack.to_obj() + "\n").map_err(|error| format!("Could not write {}: {}", path, error))?;
+            Ok(format!("Wrote {} bricks to {}", stack.bricks.len(), path))
+        }
+        _ => Err(String::from("Mode should be one of on, fall, safe, coords or obj"))
+    }
+}
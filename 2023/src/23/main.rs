@@ -1,14 +1,17 @@
 // Idea for part 1 from https://github.com/hyper-neutrino/advent-of-code/blob/main/2023/day23p1.py
 
-use std::{fs, ops::{AddAssign, Add}, collections::{HashMap, VecDeque, HashSet}, vec, cmp::max};
+use std::{fs, env, ops::{AddAssign, Add}, collections::{HashMap, VecDeque, HashSet}, vec, cmp::max, thread, sync::{Mutex, atomic::{AtomicU64, Ordering}}};
 
 const FILE_PATH : &str = "input.txt";
+const PARALLEL_STATES: usize = 64;
 
 type Row = Vec<Obstacle>;
 type Matrix = Vec<Row>;
 type Edges = Vec<Position>;
 type Directions = Vec<Direction>;
 type Graph = HashMap<Position, HashMap<Position, u64>>;
+type Mask = u64;
+type Route = Vec<usize>;
 
 #[derive(PartialEq, Eq)]
 enum Obstacle
@@ -124,6 +127,19 @@ impl Position
     }
 }
 
+fn to_char(obstacle: &Obstacle) -> char
+{
+    return match obstacle
+    {
+        Obstacle::Path => '.',
+        Obstacle::Forest => '#',
+        Obstacle::SlopeLeft => '<',
+        Obstacle::SlopeRight => '>',
+        Obstacle::SlopeUp => '^',
+        Obstacle::SlopeDown => 'v'
+    }
+}
+
 fn convert(c: char) -> Obstacle
 {
     return match c
@@ -259,41 +275,333 @@ fn create_graph<TPart: ObstacleToPosition>(matrix: &Matrix, edge_contractions: &
     return graph;
 }
 
-fn dfs(graph: &Graph, seen: &mut HashSet<Position>, position: &Position, end: &Position) -> u64
+// Junctions re-indexed densely so the visited set fits in a single mask
+struct JunctionGraph
+{
+    neighbors: Vec<Vec<(usize, u64)>>,
+    best_entries: Vec<u64>,
+    start: usize,
+    end: usize,
+    exit: Option<usize>
+}
+
+#[derive(Clone, Copy)]
+struct State
+{
+    junction: usize,
+    visited: Mask,
+    length: u64,
+    bound: u64
+}
+
+fn index_graph(graph: &Graph, edge_contractions: &Edges) -> JunctionGraph
+{
+    assert!(edge_contractions.len() <= Mask::BITS as usize, "Only up to {} junctions are supported", Mask::BITS);
+
+    let indices: HashMap<Position, usize> = edge_contractions.iter().enumerate().map(|(i, position)| (*position, i)).collect();
+    let mut neighbors: Vec<Vec<(usize, u64)>> = vec![Vec::new(); edge_contractions.len()];
+    let mut best_entries: Vec<u64> = vec![0; edge_contractions.len()];
+
+    for (position, edges) in graph
+    {
+        let junction = indices[position];
+
+        for (next_position, weight) in edges
+        {
+            let next = indices[next_position];
+            neighbors[junction].push((next, *weight));
+            best_entries[next] = max(best_entries[next], *weight);
+        }
+    }
+
+    let (start, end) = (indices[&edge_contractions[0]], indices[&edge_contractions[1]]);
+    let entries: Vec<usize> = (0..neighbors.len()).filter(|&i| i != end && neighbors[i].iter().any(|edge| edge.0 == end)).collect();
+
+    // When only one junction leads to the exit, leaving it any other way can never reach the exit again
+    let exit: Option<usize> = if entries.len() == 1 { Some(entries[0]) } else { None };
+
+    return JunctionGraph{neighbors: neighbors, best_entries: best_entries, start: start, end: end, exit: exit};
+}
+
+struct Best
+{
+    length: AtomicU64,
+    route: Mutex<Route>
+}
+
+struct Hike
+{
+    length: u64,
+    junctions: Edges,
+    tiles: Edges
+}
+
+fn advance(graph: &JunctionGraph, state: &State, edge: usize) -> Option<State>
+{
+    let (next, weight) = graph.neighbors[state.junction][edge];
+
+    if state.visited & (1 << next) != 0 || (Some(state.junction) == graph.exit && next != graph.end)
+    {
+        return None;
+    }
+
+    return Some(State{
+        junction: next,
+        visited: state.visited | (1 << next),
+        length: state.length + weight,
+        bound: state.bound - graph.best_entries[next]
+    });
+}
+
+fn record(best: &Best, length: u64, route: Route)
+{
+    let mut best_route = best.route.lock().unwrap();
+
+    if length > best.length.load(Ordering::Relaxed)
+    {
+        best.length.store(length, Ordering::Relaxed);
+        *best_route = route;
+    }
+}
+
+// Every junction still to visit is entered through one edge, so the best entries bound what is left of the path
+fn dfs(graph: &JunctionGraph, state: &State, route: &Route, best: &Best)
+{
+    let mut stack: Vec<(State, usize)> = vec![(*state, 0)];
+
+    while !stack.is_empty()
+    {
+        let (current, edge) = *stack.last().unwrap();
+
+        if edge == graph.neighbors[current.junction].len()
+        {
+            stack.pop();
+            continue;
+        }
+
+        stack.last_mut().unwrap().1 += 1;
+
+        let next_option = advance(graph, &current, edge);
+
+        if next_option.is_none()
+        {
+            continue;
+        }
+
+        let next: State = next_option.unwrap();
+
+        if next.junction == graph.end
+        {
+            if next.length > best.length.load(Ordering::Relaxed)
+            {
+                let mut full_route: Route = route.clone();
+                full_route.extend(stack.iter().skip(1).map(|frame| frame.0.junction));
+                full_route.push(next.junction);
+                record(best, next.length, full_route);
+            }
+
+            continue;
+        }
+
+        if next.length + next.bound > best.length.load(Ordering::Relaxed)
+        {
+            stack.push((next, 0));
+        }
+    }
+}
+
+fn longest_path(graph: &JunctionGraph) -> (u64, Route)
+{
+    let best: Best = Best{length: AtomicU64::new(0), route: Mutex::new(Route::new())};
+    let bound: u64 = (0..graph.neighbors.len()).filter(|&i| i != graph.start).map(|i| graph.best_entries[i]).sum();
+    let mut frontier: Vec<(State, Route)> = vec![(State{junction: graph.start, visited: 1 << graph.start, length: 0, bound: bound}, vec![graph.start])];
+
+    // Expand the first levels breadth first so each thread gets its own share of the search
+    while !frontier.is_empty() && frontier.len() < PARALLEL_STATES
+    {
+        let mut next_frontier: Vec<(State, Route)> = Vec::new();
+
+        for (state, route) in frontier
+        {
+            if state.junction == graph.end
+            {
+                record(&best, state.length, route);
+                continue;
+            }
+
+            for edge in 0..graph.neighbors[state.junction].len()
+            {
+                let next_option = advance(graph, &state, edge);
+
+                if next_option.is_some()
+                {
+                    let next: State = next_option.unwrap();
+                    let mut next_route: Route = route.clone();
+                    next_route.push(next.junction);
+                    next_frontier.push((next, next_route));
+                }
+            }
+        }
+
+        frontier = next_frontier;
+    }
+
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+
+    thread::scope(|scope|
+    {
+        for chunk in frontier.chunks(frontier.len().div_ceil(threads).max(1))
+        {
+            let best = &best;
+            scope.spawn(move ||
+            {
+                for (state, route) in chunk
+                {
+                    dfs(graph, state, route, best);
+                }
+            });
+        }
+    });
+
+    return (best.length.into_inner(), best.route.into_inner().unwrap());
+}
+
+fn trace_edge<TPart: ObstacleToPosition>(matrix: &Matrix, edge_contractions: &Edges, from: &Position, to: &Position) -> Edges
 {
-    if position == end
+    let mut parents: HashMap<Position, Position> = HashMap::new();
+    let mut queue: VecDeque<Position> = vec![*from].into();
+    parents.insert(*from, *from);
+
+    while !queue.is_empty()
+    {
+        let position: Position = queue.pop_front().unwrap();
+
+        if position == *to
+        {
+            break;
+        }
+
+        if position != *from && edge_contractions.contains(&position)
+        {
+            continue;
+        }
+
+        for direction in TPart::convert(&matrix[position.x as usize][position.y as usize])
+        {
+            let next_position = position + direction.convert();
+            if next_position.is_valid(&matrix) && !parents.contains_key(&next_position)
+            {
+                parents.insert(next_position, position);
+                queue.push_back(next_position);
+            }
+        }
+    }
+
+    let mut tiles: Edges = vec![*to];
+
+    while tiles.last().unwrap() != from
     {
-        return 0;
+        tiles.push(parents[tiles.last().unwrap()]);
     }
 
-    let mut max_path = std::i64::MIN;
-    seen.insert(*position);
+    tiles.reverse();
+    return tiles;
+}
+
+fn hike<TPart: ObstacleToPosition>(matrix: &Matrix, edge_contractions: &Edges) -> Hike
+{
+    let graph: Graph = create_graph::<TPart>(&matrix, &edge_contractions);
+    let (length, route) = longest_path(&index_graph(&graph, &edge_contractions));
+    let junctions: Edges = route.iter().map(|&junction| edge_contractions[junction]).collect();
+    let mut tiles: Edges = junctions.iter().take(1).copied().collect();
 
-    for next_position in graph.get(position).unwrap().keys()
+    for pair in junctions.windows(2)
     {
-        if !seen.contains(next_position)
+        tiles.extend(trace_edge::<TPart>(matrix, edge_contractions, &pair[0], &pair[1]).into_iter().skip(1));
+    }
+
+    debug_assert!(tiles.is_empty() || tiles.len() as u64 == length + 1);
+
+    return Hike{length: length, junctions: junctions, tiles: tiles};
+}
+
+fn sorted_edges(graph: &Graph) -> Vec<(Position, Position, u64)>
+{
+    let mut edges: Vec<(Position, Position, u64)> = Vec::new();
+
+    for (from, neighbors) in graph
+    {
+        for (to, weight) in neighbors
         {
-            max_path = max(max_path, (dfs(&graph, seen, next_position, &end) + graph.get(position).unwrap().get(next_position).unwrap()) as i64);
+            edges.push((*from, *to, *weight));
         }
     }
 
-    seen.remove(position);
+    edges.sort_by_key(|edge| (edge.0.x, edge.0.y, edge.1.x, edge.1.y));
+    return edges;
+}
+
+fn to_dot(graph: &Graph) -> String
+{
+    let mut dot: String = String::from("digraph junctions {\n");
+
+    for (from, to, weight) in sorted_edges(graph)
+    {
+        dot += &format!("    \"{},{}\" -> \"{},{}\" [label={}];\n", from.x, from.y, to.x, to.y, weight);
+    }
 
-    return max_path as u64;
+    dot += "}";
+    return dot;
 }
 
-fn part1(matrix: &Matrix, edge_contractions: &Edges, start: &Position, end: &Position) -> u64
+fn to_json(graph: &Graph) -> String
 {
-    let graph: Graph = create_graph::<Part1>(&matrix, &edge_contractions);
-    let mut seen: HashSet<Position> = HashSet::new();
-    return dfs(&graph, &mut seen, &start, &end);
+    let edges: Vec<String> = sorted_edges(graph).iter()
+        .map(|(from, to, weight)| format!("{{\"from\": [{}, {}], \"to\": [{}, {}], \"weight\": {}}}", from.x, from.y, to.x, to.y, weight))
+        .collect();
+
+    return format!("[\n    {}\n]", edges.join(",\n    "));
 }
 
-fn part2(matrix: &Matrix, edge_contractions: &Edges, start: &Position, end: &Position) -> u64
+fn render(matrix: &Matrix, hike: &Hike) -> String
 {
-    let graph: Graph = create_graph::<Part2>(&matrix, &edge_contractions);
-    let mut seen: HashSet<Position> = HashSet::new();
-    return dfs(&graph, &mut seen, &start, &end);
+    let mut rows: Vec<Vec<char>> = matrix.iter().map(|row| row.iter().map(|obstacle| to_char(obstacle)).collect()).collect();
+
+    for tile in &hike.tiles
+    {
+        rows[tile.x as usize][tile.y as usize] = 'O';
+    }
+
+    return rows.iter().map(|row| row.iter().collect::<String>()).collect::<Vec<String>>().join("\n");
+}
+
+fn explore<TPart: ObstacleToPosition>(matrix: &Matrix, edge_contractions: &Edges, mode: &str) -> Result<(), String>
+{
+    match mode
+    {
+        "route" =>
+        {
+            let hike: Hike = hike::<TPart>(matrix, edge_contractions);
+            println!("{}", hike.length);
+            println!("{}", hike.junctions.iter().map(|junction| format!("({}, {})", junction.x, junction.y)).collect::<Vec<String>>().join(" -> "));
+            println!("{}", render(matrix, &hike));
+        }
+        "dot" => println!("{}", to_dot(&create_graph::<TPart>(matrix, edge_contractions))),
+        "json" => println!("{}", to_json(&create_graph::<TPart>(matrix, edge_contractions))),
+        _ => return Err(String::from("Mode should be one of route, dot or json"))
+    }
+
+    return Ok(());
+}
+
+fn part1(matrix: &Matrix, edge_contractions: &Edges) -> u64
+{
+    return hike::<Part1>(matrix, edge_contractions).length;
+}
+
+fn part2(matrix: &Matrix, edge_contractions: &Edges) -> u64
+{
+    return hike::<Part2>(matrix, edge_contractions).length;
 }
 
 fn main()
@@ -305,8 +613,29 @@ fn main()
     let (start, end) = (find_start(&matrix), find_end(&matrix));
     let edge_contractions: Edges = calculate_edge_contraction(&matrix, &start, &end);
 
-    let part1_result: u64 = part1(&matrix, &edge_contractions, &start, &end);
-    let part2_result: u64 = part2(&matrix, &edge_contractions, &start, &end);
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if !args.is_empty()
+    {
+        // Usage: <route|dot|json> [1|2], e.g. "route 2"
+        let result = match args.get(1).map_or("2", |part| part.as_str())
+        {
+            "1" => explore::<Part1>(&matrix, &edge_contractions, &args[0]),
+            "2" => explore::<Part2>(&matrix, &edge_contractions, &args[0]),
+            part => Err(format!("Part should be 1 or 2, got \"{}\"", part))
+        };
+
+        if let Err(message) = result
+        {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    let part1_result: u64 = part1(&matrix, &edge_contractions);
+    let part2_result: u64 = part2(&matrix, &edge_contractions);
     
     println!("{}", part1_result);
     println!("{}", part2_result);
@@ -1,27 +1,92 @@
 // Idea for part 1 from https://github.com/hyper-neutrino/advent-of-code/blob/main/2023/day24p1.py
 // Idea for part 2 from https://github.com/tymscar/Advent-Of-Code/blob/master/2023/rust/src/day24/part2.rs
 
-use std::fs;
+use std::{fs, env, fmt::Display};
+use num::{BigInt, BigRational, Zero, ToPrimitive, rational::Ratio};
+#[cfg(feature = "z3")]
 use z3::ast::{Ast, Int};
+#[cfg(feature = "z3")]
 use z3::{Config, Context, Solver};
 
 const FILE_PATH : &str = "input.txt";
-const BOUNDS: (i64, i64) = (200000000000000, 400000000000000);
+const BOUNDS: Area = Area{min_x: 200000000000000, max_x: 400000000000000, min_y: 200000000000000, max_y: 400000000000000};
 
 type Hailstones = Vec<Hailstone>;
+type Vector = [i128; 3];
+type Equation = (Vec<i128>, i128);
+type Coordinate = Ratio<i128>;
 
 #[derive(Clone, Copy, Debug)]
 struct Hailstone
 {
-    px: f64,
-    py: f64,
-    pz: f64,
-    vx: f64,
-    vy: f64,
-    vz: f64,
-    a: f64,
-    b: f64,
-    c: f64
+    px: i128,
+    py: i128,
+    pz: i128,
+    vx: i128,
+    vy: i128,
+    vz: i128,
+    a: i128,
+    b: i128,
+    c: i128
+}
+
+struct Intersection
+{
+    first: usize,
+    second: usize,
+    x: Coordinate,
+    y: Coordinate
+}
+
+impl Display for Intersection
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        return write!(f, "{} x {} at ({}, {})", self.first, self.second, self.x, self.y);
+    }
+}
+
+struct Area
+{
+    min_x: i128,
+    max_x: i128,
+    min_y: i128,
+    max_y: i128
+}
+
+struct Approach
+{
+    time: Coordinate,
+    squared_distance: BigRational
+}
+
+impl Display for Approach
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        return write!(f, "closest at t = {} with distance {:.3}", self.time, self.squared_distance.to_f64().unwrap().sqrt());
+    }
+}
+
+struct Collision
+{
+    time: Coordinate,
+    position: [Coordinate; 3]
+}
+
+impl Display for Collision
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        return write!(f, "collide at t = {} in ({}, {}, {})", self.time, self.position[0], self.position[1], self.position[2]);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Rock
+{
+    position: Vector,
+    velocity: Vector
 }
 
 fn parse(contents: &String) -> Hailstones
@@ -32,8 +97,8 @@ fn parse(contents: &String) -> Hailstones
     {
         let data_str: Vec<&str> = line.split(" @ ").collect();
         let (position_str, velocity_str) = (data_str[0], data_str[1]);
-        let position: Vec<f64> = position_str.split(", ").map(|s| s.trim().parse::<f64>().unwrap()).collect();
-        let velocity: Vec<f64> = velocity_str.split(", ").map(|s| s.trim().parse::<f64>().unwrap()).collect();
+        let position: Vec<i128> = position_str.split(", ").map(|s| s.trim().parse::<i128>().unwrap()).collect();
+        let velocity: Vec<i128> = velocity_str.split(", ").map(|s| s.trim().parse::<i128>().unwrap()).collect();
 
         hailstones.push(Hailstone
             {
@@ -57,50 +122,250 @@ fn are_parallel(lhs: &Hailstone, rhs: &Hailstone) -> bool
     return (lhs.a * rhs.b) == (lhs.b * rhs.a);
 }
 
-fn in_bounds(x: f64, y: f64) -> bool
+fn in_bounds(x: &Coordinate, y: &Coordinate, area: &Area) -> bool
+{
+    return ((*x >= Coordinate::from_integer(area.min_x)) && (*x <= Coordinate::from_integer(area.max_x)))
+        && ((*y >= Coordinate::from_integer(area.min_y)) && (*y <= Coordinate::from_integer(area.max_y)));
+}
+
+fn is_future(hailstone: &Hailstone, x: &Coordinate, y: &Coordinate) -> bool
+{
+    return ((x - hailstone.px) * hailstone.vx >= Coordinate::zero())
+        && ((y - hailstone.py) * hailstone.vy >= Coordinate::zero());
+}
+
+fn will_intersect(hailstone: &Hailstone, other_hailstone: &Hailstone, x: &Coordinate, y: &Coordinate) -> bool
+{
+    return is_future(hailstone, x, y) && is_future(other_hailstone, x, y);
+}
+
+fn to_big(value: &Coordinate) -> BigRational
+{
+    return BigRational::new(BigInt::from(*value.numer()), BigInt::from(*value.denom()));
+}
+
+// Position and velocity of the second hailstone as seen from the first one
+fn relative(hailstone: &Hailstone, other_hailstone: &Hailstone) -> (Vector, Vector)
+{
+    let (p, v) = to_vectors(hailstone);
+    let (other_p, other_v) = to_vectors(other_hailstone);
+
+    return ([other_p[0] - p[0], other_p[1] - p[1], other_p[2] - p[2]],
+            [other_v[0] - v[0], other_v[1] - v[1], other_v[2] - v[2]]);
+}
+
+trait Trajectories
+{
+    fn closest_approach(&self, first: usize, second: usize) -> Approach;
+    fn collision(&self, first: usize, second: usize) -> Option<Collision>;
+    fn crossings_within(&self, area: &Area) -> Vec<Intersection>;
+}
+
+impl Trajectories for Hailstones
+{
+    // The squared distance is a parabola in time, so its minimum is either its vertex or the present
+    fn closest_approach(&self, first: usize, second: usize) -> Approach
+    {
+        let (dp, dv) = relative(&self[first], &self[second]);
+        let speed: i128 = dv.iter().map(|v| v * v).sum();
+        let closing: i128 = (0..3).map(|axis| dp[axis] * dv[axis]).sum();
+
+        let time: Coordinate = if speed == 0 || closing >= 0 { Coordinate::zero() } else { Coordinate::new(-closing, speed) };
+        let squared_distance: BigRational = (0..3)
+            .map(|axis| to_big(&(time * dv[axis] + dp[axis])).pow(2))
+            .fold(BigRational::zero(), |sum, value| sum + value);
+
+        return Approach{time: time, squared_distance: squared_distance};
+    }
+
+    fn collision(&self, first: usize, second: usize) -> Option<Collision>
+    {
+        let (dp, dv) = relative(&self[first], &self[second]);
+        let axis_option = (0..3).find(|&axis| dv[axis] != 0);
+
+        let time: Coordinate = if axis_option.is_some() { Coordinate::new(-dp[axis_option.unwrap()], dv[axis_option.unwrap()]) } else { Coordinate::zero() };
+
+        if time < Coordinate::zero() || (0..3).any(|axis| !(time * dv[axis] + dp[axis]).is_zero())
+        {
+            return None;
+        }
+
+        let (p, v) = to_vectors(&self[first]);
+        let position: [Coordinate; 3] = [time * v[0] + p[0], time * v[1] + p[1], time * v[2] + p[2]];
+
+        return Some(Collision{time: time, position: position});
+    }
+
+    fn crossings_within(&self, area: &Area) -> Vec<Intersection>
+    {
+        let mut intersections: Vec<Intersection> = Vec::new();
+
+        for (i, hailstone) in self.iter().enumerate()
+        {
+            for (j, other_hailstone) in self[0..i].iter().enumerate()
+            {
+                if are_parallel(hailstone, other_hailstone)
+                {
+                    continue;
+                }
+
+                let determinant: i128 = (hailstone.a * other_hailstone.b) - (other_hailstone.a * hailstone.b);
+                let x: Coordinate = Coordinate::new((hailstone.c * other_hailstone.b) - (other_hailstone.c * hailstone.b), determinant);
+                let y: Coordinate = Coordinate::new((other_hailstone.c * hailstone.a) - (hailstone.c * other_hailstone.a), determinant);
+
+                if in_bounds(&x, &y, area) && will_intersect(&hailstone, &other_hailstone, &x, &y)
+                {
+                    intersections.push(Intersection{first: j, second: i, x: x, y: y});
+                }
+            }
+        }
+
+        return intersections;
+    }
+}
+
+fn to_vectors(hailstone: &Hailstone) -> (Vector, Vector)
+{
+    return ([hailstone.px, hailstone.py, hailstone.pz],
+            [hailstone.vx, hailstone.vy, hailstone.vz]);
+}
+
+fn cross(lhs: &Vector, rhs: &Vector) -> Vector
 {
-    return ((x >= BOUNDS.0 as f64) && (x <= BOUNDS.1 as f64))
-        && ((y >= BOUNDS.0 as f64) && (y <= BOUNDS.1 as f64));
+    return [lhs[1] * rhs[2] - lhs[2] * rhs[1],
+            lhs[2] * rhs[0] - lhs[0] * rhs[2],
+            lhs[0] * rhs[1] - lhs[1] * rhs[0]];
 }
 
-fn will_intersect(hailstone: &Hailstone, other_hailstone: &Hailstone, x: f64, y: f64) -> bool
+// The rock hits every hailstone, so (P - p) x (V - v) = 0 for each of them. Subtracting that for two
+// hailstones cancels the P x V term and leaves 3 linear equations over P and V:
+// P x (v' - v) + (p' - p) x V = p' x v' - p x v
+fn pair_equations(hailstone: &Hailstone, other_hailstone: &Hailstone) -> Vec<Equation>
 {
-    return (((x - hailstone.px as f64) * hailstone.vx as f64) >= 0.0)
-        && (((y - hailstone.py as f64) * hailstone.vy as f64) >= 0.0)
-        && (((x - other_hailstone.px as f64) * other_hailstone.vx as f64) >= 0.0)
-        && (((y - other_hailstone.py as f64) * other_hailstone.vy as f64) >= 0.0);
+    let (p, v) = to_vectors(hailstone);
+    let (other_p, other_v) = to_vectors(other_hailstone);
+
+    let dp: Vector = [other_p[0] - p[0], other_p[1] - p[1], other_p[2] - p[2]];
+    let dv: Vector = [other_v[0] - v[0], other_v[1] - v[1], other_v[2] - v[2]];
+    let (other_moment, moment) = (cross(&other_p, &other_v), cross(&p, &v));
+
+    return vec![
+        (vec![0, dv[2], -dv[1], 0, -dp[2], dp[1]], other_moment[0] - moment[0]),
+        (vec![-dv[2], 0, dv[0], dp[2], 0, -dp[0]], other_moment[1] - moment[1]),
+        (vec![dv[1], -dv[0], 0, -dp[1], dp[0], 0], other_moment[2] - moment[2])
+    ];
 }
 
-fn calculate_intersections(hailstones: &Hailstones) -> u64
+fn gaussian_elimination(equations: &Vec<Equation>) -> Option<Vec<BigRational>>
 {
-    let mut result: u64 = 0;
+    let size = equations.len();
+    let mut matrix: Vec<Vec<BigRational>> = equations.iter()
+        .map(|(coefficients, constant)| coefficients.iter().chain([constant]).map(|&value| BigRational::from_integer(BigInt::from(value))).collect())
+        .collect();
 
-    for (i, hailstone) in hailstones.iter().enumerate()
+    for column in 0..size
     {
-        for other_hailstone in hailstones[0..i].iter()
+        let pivot = (column..size).find(|&row| !matrix[row][column].is_zero())?;
+        matrix.swap(column, pivot);
+
+        for row in 0..size
         {
-            if are_parallel(hailstone, other_hailstone)
+            if row == column || matrix[row][column].is_zero()
             {
                 continue;
             }
 
-            let x: f64 = ((hailstone.c * other_hailstone.b) - (other_hailstone.c * hailstone.b)) / ((hailstone.a * other_hailstone.b) - (other_hailstone.a * hailstone.b));
-            let y: f64 = ((other_hailstone.c * hailstone.a) - (hailstone.c * other_hailstone.a)) / ((hailstone.a * other_hailstone.b) - (other_hailstone.a * hailstone.b));
-            
-            if in_bounds(x, y)
+            let factor: BigRational = &matrix[row][column] / &matrix[column][column];
+
+            for k in column..=size
             {
-                if will_intersect(&hailstone, &other_hailstone, x, y)
+                let value: BigRational = &factor * &matrix[column][k];
+                matrix[row][k] -= value;
+            }
+        }
+    }
+
+    return Some((0..size).map(|row| &matrix[row][size] / &matrix[row][row]).collect());
+}
+
+fn hits(rock: &Rock, hailstone: &Hailstone) -> bool
+{
+    let (p, v) = to_vectors(hailstone);
+    let offset: Vector = [rock.position[0] - p[0], rock.position[1] - p[1], rock.position[2] - p[2]];
+    let closing: Vector = [v[0] - rock.velocity[0], v[1] - rock.velocity[1], v[2] - rock.velocity[2]];
+
+    if cross(&offset, &closing) != [0, 0, 0]
+    {
+        return false;
+    }
+
+    let axis_option = (0..3).find(|&axis| closing[axis] != 0);
+
+    if axis_option.is_none()
+    {
+        return offset == [0, 0, 0];
+    }
+
+    let axis = axis_option.unwrap();
+    return offset[axis] % closing[axis] == 0 && offset[axis] / closing[axis] >= 0;
+}
+
+// A degenerate triple can give a fractional or wrong rock, so keep trying until one hits every hailstone
+fn find_rock(hailstones: &Hailstones) -> Result<Rock, String>
+{
+    let mut failure: String = String::from("No three hailstones pin down a single rock");
+
+    for i in 0..hailstones.len()
+    {
+        for j in i + 1..hailstones.len()
+        {
+            for k in j + 1..hailstones.len()
+            {
+                let mut equations: Vec<Equation> = pair_equations(&hailstones[i], &hailstones[j]);
+                equations.extend(pair_equations(&hailstones[i], &hailstones[k]));
+
+                let solution_option = gaussian_elimination(&equations);
+
+                if solution_option.is_none()
+                {
+                    continue;
+                }
+
+                let solution: Vec<BigRational> = solution_option.unwrap();
+
+                if solution.iter().any(|value| !value.is_integer())
+                {
+                    failure = format!("Hailstones {}, {} and {} give a rock with fractional coordinates", i, j, k);
+                    continue;
+                }
+
+                let values: Vec<i128> = solution.iter()
+                    .map(|value| i128::try_from(value.to_integer()).map_err(|_| String::from("Rock coordinates overflow")))
+                    .collect::<Result<Vec<i128>, String>>()?;
+
+                let rock: Rock = Rock{position: [values[0], values[1], values[2]], velocity: [values[3], values[4], values[5]]};
+
+                match hailstones.iter().position(|hailstone| !hits(&rock, hailstone))
                 {
-                    result += 1;
+                    Some(index) => failure = format!("Rock {:?} derived from hailstones {}, {} and {} misses hailstone {}", rock, i, j, k, index),
+                    None => return Ok(rock)
                 }
             }
         }
     }
-    
-    return result;
+
+    return Err(failure);
 }
 
 fn solve(hailstones: &Hailstones) -> u64
+{
+    let rock: Rock = find_rock(hailstones)
+        .unwrap_or_else(|reason| panic!("Can't find the rock: {}", reason));
+    return (rock.position[0] + rock.position[1] + rock.position[2]) as u64;
+}
+
+#[cfg(feature = "z3")]
+fn solve_z3(hailstones: &Hailstones) -> u64
 {
     let config = Config::new();
     let context = Context::new(&config);
@@ -139,12 +404,59 @@ fn solve(hailstones: &Hailstones) -> u64
 
 fn part1(hailstones: &Hailstones) -> u64
 {
-    return calculate_intersections(&hailstones);
+    return hailstones.crossings_within(&BOUNDS).len() as u64;
 }
 
 fn part2(hailstones: &Hailstones) -> u64
 {
-    return solve(&hailstones);
+    let result: u64 = solve(&hailstones);
+
+    #[cfg(feature = "z3")]
+    assert_eq!(result, solve_z3(&hailstones), "Exact solver and z3 disagree");
+
+    return result;
+}
+
+const USAGE: &str = "Usage: intersections | approach <i> <j> | collision <i> <j> | crossings <min x> <max x> <min y> <max y>";
+
+fn number(args: &Vec<String>, n: usize) -> Result<i128, String>
+{
+    let arg = args.get(n).ok_or(format!("Missing argument {}", n))?;
+    return arg.parse::<i128>().map_err(|_| format!("Argument \"{}\" should be a number", arg));
+}
+
+fn index(hailstones: &Hailstones, args: &Vec<String>, n: usize) -> Result<usize, String>
+{
+    let value = number(args, n)?;
+
+    if value < 0 || value as usize >= hailstones.len()
+    {
+        return Err(format!("Hailstone {} is out of range, there are {}", value, hailstones.len()));
+    }
+
+    return Ok(value as usize);
+}
+
+fn explore(hailstones: &Hailstones, args: &Vec<String>) -> Result<(), String>
+{
+    match args[0].as_str()
+    {
+        "intersections" => hailstones.crossings_within(&BOUNDS).iter().for_each(|intersection| println!("{}", intersection)),
+        "approach" => println!("{}", hailstones.closest_approach(index(hailstones, args, 1)?, index(hailstones, args, 2)?)),
+        "collision" => match hailstones.collision(index(hailstones, args, 1)?, index(hailstones, args, 2)?)
+        {
+            Some(collision) => println!("{}", collision),
+            None => println!("no collision")
+        },
+        "crossings" =>
+        {
+            let area: Area = Area{min_x: number(args, 1)?, max_x: number(args, 2)?, min_y: number(args, 3)?, max_y: number(args, 4)?};
+            hailstones.crossings_within(&area).iter().for_each(|intersection| println!("{}", intersection));
+        }
+        _ => return Err(String::from("Mode should be one of intersections, approach, collision or crossings"))
+    }
+
+    return Ok(());
 }
 
 fn main()
@@ -154,6 +466,19 @@ fn main()
 
     let hailstones: Hailstones = parse(&contents);
 
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if !args.is_empty()
+    {
+        if let Err(message) = explore(&hailstones, &args)
+        {
+            eprintln!("{}\n{}", message, USAGE);
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
     let part1_result: u64 = part1(&hailstones);
     let part2_result: u64 = part2(&hailstones);
     
@@ -0,0 +1,267 @@
+use num::integer::gcd;
+
+pub type Vertex = (i64, i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation
+{
+    Clockwise,
+    CounterClockwise,
+    Degenerate
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Containment
+{
+    Inside,
+    Boundary,
+    Outside
+}
+
+// Closed polygon over integer vertices. All products are done in i128 so i64 coordinates can't overflow
+pub struct Polygon
+{
+    vertices: Vec<Vertex>
+}
+
+fn widen(vertex: &Vertex) -> (i128, i128)
+{
+    return (vertex.0 as i128, vertex.1 as i128);
+}
+
+fn cross(origin: &Vertex, lhs: &Vertex, rhs: &Vertex) -> i128
+{
+    let (o, l, r) = (widen(origin), widen(lhs), widen(rhs));
+    return (l.0 - o.0) * (r.1 - o.1) - (l.1 - o.1) * (r.0 - o.0);
+}
+
+fn dot(origin: &Vertex, lhs: &Vertex, rhs: &Vertex) -> i128
+{
+    let (o, l, r) = (widen(origin), widen(lhs), widen(rhs));
+    return (l.0 - o.0) * (r.0 - o.0) + (l.1 - o.1) * (r.1 - o.1);
+}
+
+fn on_segment(start: &Vertex, end: &Vertex, point: &Vertex) -> bool
+{
+    return cross(start, end, point) == 0
+        && point.0 >= start.0.min(end.0) && point.0 <= start.0.max(end.0)
+        && point.1 >= start.1.min(end.1) && point.1 <= start.1.max(end.1);
+}
+
+fn segments_intersect(a: &Vertex, b: &Vertex, c: &Vertex, d: &Vertex) -> bool
+{
+    let (d1, d2) = (cross(a, b, c).signum(), cross(a, b, d).signum());
+    let (d3, d4) = (cross(c, d, a).signum(), cross(c, d, b).signum());
+
+    if d1 * d2 < 0 && d3 * d4 < 0
+    {
+        return true;
+    }
+
+    return on_segment(a, b, c) || on_segment(a, b, d) || on_segment(c, d, a) || on_segment(c, d, b);
+}
+
+impl Polygon
+{
+    // A repeated first vertex at the end, as left by walking a closed path, is dropped
+    pub fn new(mut vertices: Vec<Vertex>) -> Polygon
+    {
+        if vertices.len() > 1 && vertices.first() == vertices.last()
+        {
+            vertices.pop();
+        }
+
+        return Polygon{vertices};
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (&Vertex, &Vertex)>
+    {
+        return self.vertices.iter().zip(self.vertices.iter().cycle().skip(1));
+    }
+
+    // Twice the shoelace area, positive when counter-clockwise with the y axis pointing up
+    pub fn doubled_signed_area(&self) -> i128
+    {
+        return self.edges().map(|(lhs, rhs)| cross(&(0, 0), lhs, rhs)).sum();
+    }
+
+    pub fn area(&self) -> i128
+    {
+        return self.doubled_signed_area().abs() / 2;
+    }
+
+    pub fn perimeter(&self) -> f64
+    {
+        return self.edges().map(|(lhs, rhs)| ((rhs.0 - lhs.0) as f64).hypot((rhs.1 - lhs.1) as f64)).sum();
+    }
+
+    pub fn boundary_points(&self) -> i128
+    {
+        return self.edges().map(|(lhs, rhs)| gcd((rhs.0 as i128 - lhs.0 as i128).abs(), (rhs.1 as i128 - lhs.1 as i128).abs())).sum();
+    }
+
+    // Pick's theorem: A = I + B / 2 - 1
+    pub fn interior_points(&self) -> i128
+    {
+        return (self.doubled_signed_area().abs() - self.boundary_points() + 2) / 2;
+    }
+
+    pub fn orientation(&self) -> Orientation
+    {
+        return match self.doubled_signed_area().signum()
+        {
+            1 => Orientation::CounterClockwise,
+            -1 => Orientation::Clockwise,
+            _ => Orientation::Degenerate
+        }
+    }
+
+    // Crossing number over half-open edges, so a ray through a vertex is only counted once
+    pub fn contains(&self, point: &Vertex) -> Containment
+    {
+        let mut inside = false;
+
+        for (start, end) in self.edges()
+        {
+            if on_segment(start, end, point)
+            {
+                return Containment::Boundary;
+            }
+
+            if (start.1 > point.1) != (end.1 > point.1)
+            {
+                let side = cross(start, end, point);
+
+                if (side > 0) == (end.1 > start.1)
+                {
+                    inside = !inside;
+                }
+            }
+        }
+
+        return if inside { Containment::Inside } else { Containment::Outside };
+    }
+
+    pub fn is_self_intersecting(&self) -> bool
+    {
+        let count = self.vertices.len();
+
+        for i in 0..count
+        {
+            let (a, b) = (&self.vertices[i], &self.vertices[(i + 1) % count]);
+
+            // Consecutive edges only share their common vertex unless the path folds back on itself
+            let c = &self.vertices[(i + 2) % count];
+            if count > 2 && cross(b, a, c) == 0 && dot(b, a, c) > 0
+            {
+                return true;
+            }
+
+            for j in i + 2..count
+            {
+                if i == 0 && j == count - 1
+                {
+                    continue;
+                }
+
+                if segments_intersect(a, b, &self.vertices[j], &self.vertices[(j + 1) % count])
+                {
+                    return true;
+                }
+            }
+        }
+
+        return false;
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn unit_square() -> Polygon
+    {
+        return Polygon::new(vec![(0, 0), (1, 0), (1, 1), (0, 1)]);
+    }
+
+    // A 4 x 4 square with its top right quarter cut out
+    fn l_shape() -> Polygon
+    {
+        return Polygon::new(vec![(0, 0), (4, 0), (4, 2), (2, 2), (2, 4), (0, 4)]);
+    }
+
+    fn bow_tie() -> Polygon
+    {
+        return Polygon::new(vec![(0, 0), (2, 2), (2, 0), (0, 2)]);
+    }
+
+    #[test]
+    fn unit_square_measures()
+    {
+        let square = unit_square();
+
+        assert_eq!(square.area(), 1);
+        assert_eq!(square.boundary_points(), 4);
+        assert_eq!(square.interior_points(), 0);
+        assert_eq!(square.orientation(), Orientation::CounterClockwise);
+        assert_eq!(Polygon::new(vec![(0, 0), (0, 1), (1, 1), (1, 0)]).orientation(), Orientation::Clockwise);
+        assert_eq!(Polygon::new(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]).boundary_points(), 4);
+    }
+
+    #[test]
+    fn unit_square_contains()
+    {
+        let square = unit_square();
+
+        assert_eq!(square.contains(&(0, 0)), Containment::Boundary);
+        assert_eq!(square.contains(&(1, 0)), Containment::Boundary);
+        assert_eq!(square.contains(&(2, 0)), Containment::Outside);
+        assert_eq!(square.contains(&(-1, 1)), Containment::Outside);
+        assert_eq!(Polygon::new(vec![(0, 0), (2, 0), (2, 2), (0, 2)]).contains(&(1, 1)), Containment::Inside);
+    }
+
+    #[test]
+    fn concave_l_shape()
+    {
+        let shape = l_shape();
+
+        assert_eq!(shape.area(), 12);
+        assert_eq!(shape.interior_points(), 5);
+        assert_eq!(shape.orientation(), Orientation::CounterClockwise);
+        assert!(!shape.is_self_intersecting());
+
+        assert_eq!(shape.contains(&(1, 3)), Containment::Inside);
+        assert_eq!(shape.contains(&(3, 1)), Containment::Inside);
+        assert_eq!(shape.contains(&(3, 3)), Containment::Outside);
+        assert_eq!(shape.contains(&(3, 2)), Containment::Boundary);
+        assert_eq!(shape.contains(&(2, 3)), Containment::Boundary);
+    }
+
+    #[test]
+    fn ray_through_vertex()
+    {
+        let diamond = Polygon::new(vec![(2, 0), (4, 2), (2, 4), (0, 2)]);
+
+        assert_eq!(diamond.contains(&(1, 2)), Containment::Inside);
+        assert_eq!(diamond.contains(&(-1, 2)), Containment::Outside);
+        assert_eq!(diamond.contains(&(5, 2)), Containment::Outside);
+        assert_eq!(diamond.contains(&(4, 2)), Containment::Boundary);
+
+        // The row y = 2 runs through the L-shape's inner corner and its right vertex
+        assert_eq!(l_shape().contains(&(1, 2)), Containment::Inside);
+        assert_eq!(l_shape().contains(&(5, 2)), Containment::Outside);
+        assert_eq!(l_shape().contains(&(-1, 2)), Containment::Outside);
+    }
+
+    #[test]
+    fn bow_tie_intersects_itself()
+    {
+        let shape = bow_tie();
+
+        assert!(shape.is_self_intersecting());
+        assert_eq!(shape.orientation(), Orientation::Degenerate);
+        assert!(!unit_square().is_self_intersecting());
+        assert!(Polygon::new(vec![(0, 0), (2, 0), (1, 0), (1, 2)]).is_self_intersecting());
+    }
+}
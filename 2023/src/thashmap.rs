@@ -0,0 +1,469 @@
+use std::{borrow::Borrow, fmt::Display};
+
+pub const DEFAULT_BUCKETS: usize = 256;
+
+type Bucket<TKey, TValue> = Vec<(TKey, TValue)>;
+
+pub trait THash<TKey: ?Sized>
+{
+    fn hash(&self, key: &TKey) -> u64;
+}
+
+// The HASH algorithm from day 15: for each character, add its code, multiply by 17 and keep the remainder of 256
+#[derive(Default, Clone, Copy)]
+pub struct HolidayHash;
+
+impl THash<str> for HolidayHash
+{
+    fn hash(&self, key: &str) -> u64
+    {
+        let mut result: u64 = 0;
+
+        for c in key.chars()
+        {
+            result = ((result + c as u64) * 17) % 256;
+        }
+
+        return result;
+    }
+}
+
+impl THash<String> for HolidayHash
+{
+    fn hash(&self, key: &String) -> u64
+    {
+        return THash::<str>::hash(self, key.as_str());
+    }
+}
+
+// Separate chaining map whose buckets keep their entries in insertion order. Updating a key keeps its place
+pub struct THashMap<TKey, TValue, THasher = HolidayHash>
+{
+    buckets: Vec<Bucket<TKey, TValue>>,
+    hasher: THasher,
+    len: usize
+}
+
+// A view into a single slot of the map, in the same shape as std's hash_map::Entry
+pub enum Entry<'a, TKey, TValue>
+{
+    Occupied(OccupiedEntry<'a, TKey, TValue>),
+    Vacant(VacantEntry<'a, TKey, TValue>)
+}
+
+pub struct OccupiedEntry<'a, TKey, TValue>
+{
+    bucket: &'a mut Bucket<TKey, TValue>,
+    position: usize,
+    len: &'a mut usize
+}
+
+pub struct VacantEntry<'a, TKey, TValue>
+{
+    bucket: &'a mut Bucket<TKey, TValue>,
+    key: TKey,
+    len: &'a mut usize
+}
+
+impl<TKey, TValue, THasher: Default> THashMap<TKey, TValue, THasher>
+{
+    pub fn new(bucket_count: usize) -> Self
+    {
+        return Self::with_hasher(bucket_count, THasher::default());
+    }
+}
+
+impl<TKey, TValue, THasher> THashMap<TKey, TValue, THasher>
+{
+    pub fn with_hasher(bucket_count: usize, hasher: THasher) -> Self
+    {
+        assert!(bucket_count > 0, "A map needs at least one bucket");
+
+        return Self{buckets: (0..bucket_count).map(|_| Bucket::new()).collect(), hasher, len: 0};
+    }
+
+    pub fn bucket_count(&self) -> usize
+    {
+        return self.buckets.len();
+    }
+
+    pub fn len(&self) -> usize
+    {
+        return self.len;
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        return self.len == 0;
+    }
+
+    pub fn bucket(&self, index: usize) -> &[(TKey, TValue)]
+    {
+        return &self.buckets[index];
+    }
+
+    // Non-empty buckets along with their index, each in insertion order
+    pub fn buckets(&self) -> impl Iterator<Item = (usize, &[(TKey, TValue)])>
+    {
+        return self.buckets.iter().enumerate().filter(|(_, bucket)| !bucket.is_empty()).map(|(i, bucket)| (i, bucket.as_slice()));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&TKey, &TValue)>
+    {
+        return self.buckets.iter().flatten().map(|(key, value)| (key, value));
+    }
+
+    pub fn clear(&mut self)
+    {
+        self.buckets.iter_mut().for_each(|bucket| bucket.clear());
+        self.len = 0;
+    }
+}
+
+impl<TKey, TValue, THasher> THashMap<TKey, TValue, THasher>
+    where
+        TKey: PartialEq,
+        THasher: THash<TKey>
+{
+    fn index_of<TQuery: ?Sized>(&self, key: &TQuery) -> usize
+        where
+            THasher: THash<TQuery>
+    {
+        return (self.hasher.hash(key) % self.buckets.len() as u64) as usize;
+    }
+
+    fn position<TQuery: ?Sized + PartialEq>(&self, index: usize, key: &TQuery) -> Option<usize>
+        where
+            TKey: Borrow<TQuery>
+    {
+        return self.buckets[index].iter().position(|(it, _)| it.borrow() == key);
+    }
+
+    pub fn get<TQuery: ?Sized + PartialEq>(&self, key: &TQuery) -> Option<&TValue>
+        where
+            TKey: Borrow<TQuery>,
+            THasher: THash<TQuery>
+    {
+        let index = self.index_of(key);
+        return self.position(index, key).map(|position| &self.buckets[index][position].1);
+    }
+
+    pub fn get_mut<TQuery: ?Sized + PartialEq>(&mut self, key: &TQuery) -> Option<&mut TValue>
+        where
+            TKey: Borrow<TQuery>,
+            THasher: THash<TQuery>
+    {
+        let index = self.index_of(key);
+        return self.position(index, key).map(|position| &mut self.buckets[index][position].1);
+    }
+
+    // Bucket and slot currently holding the key
+    pub fn locate<TQuery: ?Sized + PartialEq>(&self, key: &TQuery) -> Option<(usize, usize)>
+        where
+            TKey: Borrow<TQuery>,
+            THasher: THash<TQuery>
+    {
+        let index = self.index_of(key);
+        return self.position(index, key).map(|position| (index, position));
+    }
+
+    pub fn contains_key<TQuery: ?Sized + PartialEq>(&self, key: &TQuery) -> bool
+        where
+            TKey: Borrow<TQuery>,
+            THasher: THash<TQuery>
+    {
+        return self.get(key).is_some();
+    }
+
+    pub fn insert(&mut self, key: TKey, value: TValue) -> Option<TValue>
+    {
+        return match self.entry(key)
+        {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) =>
+            {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    pub fn remove<TQuery: ?Sized + PartialEq>(&mut self, key: &TQuery) -> Option<TValue>
+        where
+            TKey: Borrow<TQuery>,
+            THasher: THash<TQuery>
+    {
+        let index = self.index_of(key);
+        let position = self.position(index, key)?;
+
+        self.len -= 1;
+        return Some(self.buckets[index].remove(position).1);
+    }
+
+    pub fn entry(&mut self, key: TKey) -> Entry<'_, TKey, TValue>
+    {
+        let index = self.index_of(&key);
+
+        return match self.position(index, &key)
+        {
+            Some(position) => Entry::Occupied(OccupiedEntry{bucket: &mut self.buckets[index], position, len: &mut self.len}),
+            None => Entry::Vacant(VacantEntry{bucket: &mut self.buckets[index], key, len: &mut self.len})
+        }
+    }
+}
+
+impl<'a, TKey, TValue> Entry<'a, TKey, TValue>
+{
+    pub fn key(&self) -> &TKey
+    {
+        return match self
+        {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key()
+        }
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> TValue>(self, default: F) -> &'a mut TValue
+    {
+        return match self
+        {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default())
+        }
+    }
+
+    pub fn or_insert(self, default: TValue) -> &'a mut TValue
+    {
+        return self.or_insert_with(|| default);
+    }
+
+    pub fn and_modify<F: FnOnce(&mut TValue)>(self, f: F) -> Self
+    {
+        return match self
+        {
+            Entry::Occupied(mut entry) =>
+            {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            vacant => vacant
+        }
+    }
+}
+
+impl<'a, TKey, TValue> OccupiedEntry<'a, TKey, TValue>
+{
+    pub fn key(&self) -> &TKey
+    {
+        return &self.bucket[self.position].0;
+    }
+
+    pub fn get(&self) -> &TValue
+    {
+        return &self.bucket[self.position].1;
+    }
+
+    pub fn get_mut(&mut self) -> &mut TValue
+    {
+        return &mut self.bucket[self.position].1;
+    }
+
+    pub fn into_mut(self) -> &'a mut TValue
+    {
+        return &mut self.bucket[self.position].1;
+    }
+
+    // Replaces the value in place, so the key keeps its place in the bucket
+    pub fn insert(&mut self, value: TValue) -> TValue
+    {
+        return std::mem::replace(self.get_mut(), value);
+    }
+
+    pub fn remove(self) -> TValue
+    {
+        *self.len -= 1;
+        return self.bucket.remove(self.position).1;
+    }
+}
+
+impl<'a, TKey, TValue> VacantEntry<'a, TKey, TValue>
+{
+    pub fn key(&self) -> &TKey
+    {
+        return &self.key;
+    }
+
+    pub fn into_key(self) -> TKey
+    {
+        return self.key;
+    }
+
+    // Appends to the end of the bucket, after every key already there
+    pub fn insert(self, value: TValue) -> &'a mut TValue
+    {
+        self.bucket.push((self.key, value));
+        *self.len += 1;
+        return &mut self.bucket.last_mut().unwrap().1;
+    }
+}
+
+// Same layout as the puzzle, one line per non-empty box: "Box 0: [rn 1] [cm 2]"
+impl<TKey: Display, TValue: Display, THasher> Display for THashMap<TKey, TValue, THasher>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        for (i, bucket) in self.buckets()
+        {
+            let items: Vec<String> = bucket.iter().map(|(key, value)| format!("[{} {}]", key, value)).collect();
+            writeln!(f, "Box {}: {}", i, items.join(" "))?;
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::collections::HashMap;
+
+    use super::*;
+
+    // Small xorshift generator so the property tests are reproducible without extra dependencies
+    struct Random
+    {
+        state: u64
+    }
+
+    impl Random
+    {
+        fn next(&mut self, bound: u64) -> u64
+        {
+            self.state ^= self.state << 13;
+            self.state ^= self.state >> 7;
+            self.state ^= self.state << 17;
+            return self.state % bound;
+        }
+    }
+
+    fn random_key(random: &mut Random) -> String
+    {
+        return (0..random.next(3) + 1).map(|_| (b'a' + random.next(6) as u8) as char).collect();
+    }
+
+    #[test]
+    fn holiday_hash_matches_puzzle_example()
+    {
+        assert_eq!(HolidayHash.hash("HASH"), 52);
+        assert_eq!(HolidayHash.hash(&String::from("rn=1")), 30);
+    }
+
+    #[test]
+    fn display_matches_puzzle_layout()
+    {
+        let mut map: THashMap<String, u64> = THashMap::new(DEFAULT_BUCKETS);
+
+        for (key, value) in [("rn", 1), ("cm", 2), ("ot", 9), ("ab", 5), ("pc", 6), ("ot", 7)]
+        {
+            map.insert(key.to_string(), value);
+        }
+
+        assert_eq!(map.to_string(), "Box 0: [rn 1] [cm 2]\nBox 3: [ot 7] [ab 5] [pc 6]\n");
+    }
+
+    #[test]
+    fn behaves_like_std_hashmap()
+    {
+        for seed in 1..=50
+        {
+            let mut random = Random{state: (seed as u64).wrapping_mul(0x9E3779B97F4A7C15)};
+            let mut map: THashMap<String, u64> = THashMap::new(1 + random.next(16) as usize);
+            let mut expected: HashMap<String, u64> = HashMap::new();
+
+            for _ in 0..500
+            {
+                let key = random_key(&mut random);
+                let value = random.next(100);
+
+                match random.next(6)
+                {
+                    0 | 1 => assert_eq!(map.insert(key.clone(), value), expected.insert(key, value)),
+                    2 => assert_eq!(map.remove(key.as_str()), expected.remove(&key)),
+                    3 => assert_eq!(map.get(key.as_str()), expected.get(&key)),
+                    4 =>
+                    {
+                        *map.entry(key.clone()).and_modify(|it| *it += value).or_insert(value) += 1;
+                        *expected.entry(key).and_modify(|it| *it += value).or_insert(value) += 1;
+                    }
+                    _ =>
+                    {
+                        let removed = match map.entry(key.clone())
+                        {
+                            Entry::Occupied(entry) =>
+                            {
+                                assert_eq!(entry.key(), &key);
+                                assert_eq!(Some(entry.get()), expected.get(&key));
+                                Some(entry.remove())
+                            }
+                            Entry::Vacant(entry) =>
+                            {
+                                assert_eq!(entry.key(), &key);
+                                None
+                            }
+                        };
+
+                        assert_eq!(removed, expected.remove(&key));
+                    }
+                }
+
+                assert_eq!(map.len(), expected.len());
+            }
+
+            let mut items: Vec<(String, u64)> = map.iter().map(|(key, value)| (key.clone(), *value)).collect();
+            let mut expected_items: Vec<(String, u64)> = expected.into_iter().collect();
+            items.sort();
+            expected_items.sort();
+
+            assert_eq!(items, expected_items);
+        }
+    }
+
+    #[test]
+    fn buckets_keep_insertion_order()
+    {
+        for seed in 1..=20
+        {
+            let mut random = Random{state: (seed as u64).wrapping_mul(0x2545F4914F6CDD1D)};
+            let mut map: THashMap<String, u64> = THashMap::new(4);
+            let mut order: Vec<String> = Vec::new();
+
+            for _ in 0..200
+            {
+                let key = random_key(&mut random);
+
+                if random.next(3) == 0
+                {
+                    map.remove(key.as_str());
+                    order.retain(|it| *it != key);
+                }
+                else
+                {
+                    if !map.contains_key(key.as_str())
+                    {
+                        order.push(key.clone());
+                    }
+
+                    map.insert(key, random.next(10));
+                }
+            }
+
+            for (i, bucket) in map.buckets()
+            {
+                let keys: Vec<&String> = bucket.iter().map(|(key, _)| key).collect();
+                let expected: Vec<&String> = order.iter().filter(|key| HolidayHash.hash(*key) % 4 == i as u64).collect();
+
+                assert_eq!(keys, expected);
+            }
+        }
+    }
+}